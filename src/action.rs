@@ -0,0 +1,332 @@
+use crate::app::App;
+use crate::settings::Flavor;
+
+/// Everything that can be bound to a key in normal mode.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    UpdateAddon,
+    UpdateAllAddons,
+    RemoveAddon,
+    DownloadAddon,
+    InstallAddon,
+    SearchAddon,
+    NextTab,
+    PrevTab,
+    NextTableItem,
+    PrevTableItem,
+    FirstTableItem,
+    LastTableItem,
+    Quit,
+    ScrollDownLog,
+    ScrollUpLog,
+    Autoremove,
+    EditSetting,
+    CommandPalette,
+    Help,
+    ShowDetails,
+    ShowChangelogs,
+    SortInstalled,
+    ReverseSort,
+    FilterInstalled,
+    FilterHistory,
+    ToggleMark,
+    SortSearch,
+    FilterCategory,
+    BrowseAddons,
+    SaveSearch,
+    SavedSearches,
+    ToggleLog,
+    ShowLog,
+    /// Switches to the flavor with the given index. Its key is configured in
+    /// `[[flavors]]` instead of `[keybindings]`.
+    SelectFlavor(usize),
+}
+
+pub struct ActionInfo {
+    pub action: Action,
+    /// The key in the `[keybindings]` section.
+    pub name: &'static str,
+    /// Shown in the command palette and the help.
+    pub description: &'static str,
+    /// The heading the action is listed under in the help.
+    pub group: &'static str,
+    pub run: fn(&mut App),
+}
+
+/// The dispatch table used by keybindings and the command palette. A new
+/// action only needs an entry here and a default binding in Config.toml.
+pub static ACTIONS: [ActionInfo; 33] = [
+    ActionInfo {
+        action: Action::UpdateAddon,
+        name: "update_addon",
+        description: "Update addon",
+        group: "Installed tab",
+        run: App::update_addon,
+    },
+    ActionInfo {
+        action: Action::UpdateAllAddons,
+        name: "update_all_addons",
+        description: "Update all addons",
+        group: "Installed tab",
+        run: App::update_all,
+    },
+    ActionInfo {
+        action: Action::RemoveAddon,
+        name: "remove_addon",
+        description: "Remove addon",
+        group: "Installed tab",
+        run: App::confirm_remove_addon,
+    },
+    ActionInfo {
+        action: Action::DownloadAddon,
+        name: "download_addon",
+        description: "Download addon",
+        group: "Search tab",
+        run: App::download,
+    },
+    ActionInfo {
+        action: Action::InstallAddon,
+        name: "install_addon",
+        description: "Install addon",
+        group: "Search tab",
+        run: App::download,
+    },
+    ActionInfo {
+        action: Action::SearchAddon,
+        name: "search_addon",
+        description: "Search addons",
+        group: "General",
+        run: App::select_search,
+    },
+    ActionInfo {
+        action: Action::NextTab,
+        name: "next_tab",
+        description: "Next tab",
+        group: "General",
+        run: App::select_next_tab,
+    },
+    ActionInfo {
+        action: Action::PrevTab,
+        name: "prev_tab",
+        description: "Previous tab",
+        group: "General",
+        run: App::select_prev_tab,
+    },
+    ActionInfo {
+        action: Action::NextTableItem,
+        name: "next_table_item",
+        description: "Next item",
+        group: "Tables",
+        run: App::next_table_item,
+    },
+    ActionInfo {
+        action: Action::PrevTableItem,
+        name: "prev_table_item",
+        description: "Previous item",
+        group: "Tables",
+        run: App::prev_table_item,
+    },
+    ActionInfo {
+        action: Action::FirstTableItem,
+        name: "first_table_item",
+        description: "First item",
+        group: "Tables",
+        run: App::first_table_item,
+    },
+    ActionInfo {
+        action: Action::LastTableItem,
+        name: "last_table_item",
+        description: "Last item",
+        group: "Tables",
+        run: App::last_table_item,
+    },
+    ActionInfo {
+        action: Action::Quit,
+        name: "quit",
+        description: "Quit",
+        group: "General",
+        run: App::quit,
+    },
+    ActionInfo {
+        action: Action::ScrollDownLog,
+        name: "scroll_down_log",
+        description: "Scroll log down",
+        group: "General",
+        run: App::scroll_down_log,
+    },
+    ActionInfo {
+        action: Action::ScrollUpLog,
+        name: "scroll_up_log",
+        description: "Scroll log up",
+        group: "General",
+        run: App::scroll_up_log,
+    },
+    ActionInfo {
+        action: Action::Autoremove,
+        name: "autoremove",
+        description: "Remove unused dependencies",
+        group: "Installed tab",
+        run: App::confirm_autoremove,
+    },
+    ActionInfo {
+        action: Action::EditSetting,
+        name: "edit_setting",
+        description: "Edit setting",
+        group: "Settings tab",
+        run: App::edit_setting,
+    },
+    ActionInfo {
+        action: Action::CommandPalette,
+        name: "command_palette",
+        description: "Command palette",
+        group: "General",
+        run: App::open_palette,
+    },
+    ActionInfo {
+        action: Action::Help,
+        name: "help",
+        description: "Help",
+        group: "General",
+        run: App::open_help,
+    },
+    ActionInfo {
+        action: Action::ShowDetails,
+        name: "show_details",
+        description: "Show addon details",
+        group: "Tables",
+        run: App::show_details,
+    },
+    ActionInfo {
+        action: Action::ShowChangelogs,
+        name: "show_changelogs",
+        description: "Show changelogs of outdated addons",
+        group: "Installed tab",
+        run: App::show_changelogs,
+    },
+    ActionInfo {
+        action: Action::SortInstalled,
+        name: "sort_installed",
+        description: "Sort installed addons by the next column",
+        group: "Installed tab",
+        run: App::cycle_installed_sort,
+    },
+    ActionInfo {
+        action: Action::ReverseSort,
+        name: "reverse_sort",
+        description: "Reverse the sort order",
+        group: "Installed tab",
+        run: App::reverse_installed_sort,
+    },
+    ActionInfo {
+        action: Action::FilterInstalled,
+        name: "filter_installed",
+        description: "Filter installed addons by name",
+        group: "Installed tab",
+        run: App::filter_installed,
+    },
+    ActionInfo {
+        action: Action::FilterHistory,
+        name: "filter_history",
+        description: "Filter the operation history",
+        group: "History tab",
+        run: App::filter_history,
+    },
+    ActionInfo {
+        action: Action::ToggleMark,
+        name: "toggle_mark",
+        description: "Mark or unmark the selected addon",
+        group: "Tables",
+        run: App::toggle_mark,
+    },
+    ActionInfo {
+        action: Action::SortSearch,
+        name: "sort_search",
+        description: "Sort search results by the next option",
+        group: "Search tab",
+        run: App::cycle_search_sort,
+    },
+    ActionInfo {
+        action: Action::FilterCategory,
+        name: "filter_category",
+        description: "Filter search results by category",
+        group: "Search tab",
+        run: App::open_categories,
+    },
+    ActionInfo {
+        action: Action::BrowseAddons,
+        name: "browse_addons",
+        description: "Browse all addons without a search term",
+        group: "General",
+        run: App::browse,
+    },
+    ActionInfo {
+        action: Action::SaveSearch,
+        name: "save_search",
+        description: "Save the current search",
+        group: "Search tab",
+        run: App::save_search,
+    },
+    ActionInfo {
+        action: Action::SavedSearches,
+        name: "saved_searches",
+        description: "Run a saved search",
+        group: "General",
+        run: App::open_saved_searches,
+    },
+    ActionInfo {
+        action: Action::ToggleLog,
+        name: "toggle_log",
+        description: "Collapse or expand the log",
+        group: "General",
+        run: App::toggle_log,
+    },
+    ActionInfo {
+        action: Action::ShowLog,
+        name: "show_log",
+        description: "Show the log file",
+        group: "General",
+        run: App::show_log,
+    },
+];
+
+/// The order of the groups in the help.
+pub const GROUPS: [&str; 6] = [
+    "General",
+    "Tables",
+    "Installed tab",
+    "Search tab",
+    "History tab",
+    "Settings tab",
+];
+
+impl Action {
+    fn info(self) -> Option<&'static ActionInfo> {
+        ACTIONS.iter().find(|info| info.action == self)
+    }
+
+    /// The `[keybindings]` key of the action.
+    pub fn name(self) -> &'static str {
+        self.info().map_or("select_flavor", |info| info.name)
+    }
+
+    pub fn description(self, flavors: &[Flavor]) -> String {
+        match self {
+            Action::SelectFlavor(index) => format!(
+                "Switch to {}",
+                flavors.get(index).map_or("", |f| f.name.as_str())
+            ),
+            _ => self.info().map_or("", |info| info.description).to_string(),
+        }
+    }
+
+    pub fn run(self, app: &mut App) {
+        match self {
+            Action::SelectFlavor(index) => app.select_flavor(index),
+            _ => {
+                if let Some(info) = self.info() {
+                    (info.run)(app);
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,60 @@
+use crate::logger;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs;
 use std::path::Path;
 
 const FILE_NAME: &str = ".addons.json";
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct Addons {
     pub addons: Vec<Addon>,
+    /// Maps each addon folder to the ids of all addons that ship it.
+    #[serde(default)]
+    pub folders: BTreeMap<String, Vec<String>>,
+}
+
+impl Addons {
+    fn claim_folders(&mut self, addon: &Addon) {
+        for module in addon.modules.iter() {
+            let owners = self.folders.entry(module.clone()).or_default();
+            if !owners.contains(&addon.addon_id) {
+                owners.push(addon.addon_id.clone());
+            }
+        }
+    }
+
+    /// Releases all folders of `addon` and returns the ones that are no
+    /// longer claimed by any other addon.
+    fn release_folders(&mut self, addon: &Addon) -> Vec<String> {
+        let mut unclaimed = Vec::new();
+        for module in addon.modules.iter() {
+            if let Some(owners) = self.folders.get_mut(module) {
+                owners.retain(|id| *id != addon.addon_id);
+                if !owners.is_empty() {
+                    continue;
+                }
+                self.folders.remove(module);
+            }
+            unclaimed.push(module.clone());
+        }
+        unclaimed
+    }
+
+    fn rebuild_folder_index(&mut self) {
+        self.folders.clear();
+        for addon in self.addons.clone().iter() {
+            self.claim_folders(addon);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum InstallReason {
+    #[default]
+    Explicit,
+    Dependency,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,6 +68,19 @@ pub struct Addon {
     pub version: String,
     pub game_version: String,
     pub download_count: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub install_reason: InstallReason,
+    /// When the addon was last installed, updated or reinstalled. Empty for
+    /// addons installed before this was recorded.
+    #[serde(default)]
+    pub install_date: String,
+}
+
+pub struct FolderConflict {
+    pub folder: String,
+    pub owners: Vec<String>,
 }
 
 pub struct AddonManager {}
@@ -29,7 +89,7 @@ impl AddonManager {
     pub fn init_addon_db(path: &str) -> Result<(), Box<dyn Error>> {
         let filepath = format!("{}/{}", path, FILE_NAME);
         if !Path::new(&filepath).exists() {
-            let addons = Addons { addons: Vec::new() };
+            let addons = Addons::default();
             AddonManager::save_addon_db(path, addons)?;
         }
         Ok(())
@@ -38,7 +98,10 @@ impl AddonManager {
     pub fn load_addon_db(path: &str) -> Result<Addons, Box<dyn Error>> {
         let filepath = format!("{}/{}", path, FILE_NAME);
         let content = fs::read_to_string(filepath)?;
-        let addons: Addons = serde_json::from_str(&content)?;
+        let mut addons: Addons = serde_json::from_str(&content)?;
+        if addons.folders.is_empty() && !addons.addons.is_empty() {
+            addons.rebuild_folder_index();
+        }
         Ok(addons)
     }
 
@@ -52,30 +115,223 @@ impl AddonManager {
         Ok(())
     }
 
-    pub fn add_to_db(path: &str, addon: Addon) -> Result<(), Box<dyn Error>> {
+    pub fn add_to_db(
+        path: &str,
+        mut addon: Addon,
+    ) -> Result<(), Box<dyn Error>> {
         let mut addons = AddonManager::load_addon_db(path)?;
+        addon.install_date = logger::timestamp();
+        addons.claim_folders(&addon);
         addons.addons.push(addon);
         AddonManager::save_addon_db(path, addons)?;
         Ok(())
     }
-    
+
     pub fn delete(path: &str, addon: &Addon) -> Result<(), Box<dyn Error>> {
         let mut addons = AddonManager::load_addon_db(path)?;
-        match addons
+        if let Some(index) = addons
             .addons
             .iter()
             .position(|a| a.addon_id == addon.addon_id)
         {
-            Some(index) => {
-                addons.addons.remove(index);
-                for module in addon.modules.iter() {
-                    let p = format!("{}/{}", path, module);
+            let installed = addons.addons.remove(index);
+            for module in addons.release_folders(&installed).iter() {
+                let p = Path::new(path).join(module);
+                if p.exists() {
                     fs::remove_dir_all(p)?;
                 }
             }
-            None => {}
         }
         AddonManager::save_addon_db(path, addons)?;
         Ok(())
     }
+
+    /// Returns the folders of `addon` that are already owned by other
+    /// installed addons, together with the names of their owners.
+    pub fn folder_conflicts(
+        path: &str,
+        addon: &Addon,
+    ) -> Result<Vec<FolderConflict>, Box<dyn Error>> {
+        let addons = AddonManager::load_addon_db(path)?;
+        let mut conflicts = Vec::new();
+        for module in addon.modules.iter() {
+            let owners: Vec<String> = addons
+                .folders
+                .get(module)
+                .map(|ids| {
+                    addons
+                        .addons
+                        .iter()
+                        .filter(|a| {
+                            a.addon_id != addon.addon_id
+                                && ids.contains(&a.addon_id)
+                        })
+                        .map(|a| a.name.clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            if !owners.is_empty() {
+                conflicts.push(FolderConflict {
+                    folder: module.clone(),
+                    owners,
+                });
+            }
+        }
+        Ok(conflicts)
+    }
+
+    pub fn mark_explicit(
+        path: &str,
+        addon_id: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut addons = AddonManager::load_addon_db(path)?;
+        for addon in addons.addons.iter_mut() {
+            if addon.addon_id == addon_id {
+                addon.install_reason = InstallReason::Explicit;
+            }
+        }
+        AddonManager::save_addon_db(path, addons)?;
+        Ok(())
+    }
+
+    /// Returns all addons that were only installed as a dependency and are no
+    /// longer required by any other addon. Dependencies that are only required
+    /// by other orphans are included as well.
+    pub fn find_orphans(path: &str) -> Result<Vec<Addon>, Box<dyn Error>> {
+        let mut remaining = AddonManager::load_addon_db(path)?.addons;
+        let mut orphans = Vec::new();
+        loop {
+            let (orphaned, kept): (Vec<Addon>, Vec<Addon>) =
+                remaining.iter().cloned().partition(|addon| {
+                    addon.install_reason == InstallReason::Dependency
+                        && !remaining
+                            .iter()
+                            .any(|a| a.dependencies.contains(&addon.addon_id))
+                });
+            if orphaned.is_empty() {
+                break;
+            }
+            orphans.extend(orphaned);
+            remaining = kept;
+        }
+        Ok(orphans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addon(id: &str, modules: &[&str], dependencies: &[&str]) -> Addon {
+        Addon {
+            addon_id: id.to_string(),
+            name: format!("Addon {}", id),
+            file_id: "1".to_string(),
+            file_date: String::new(),
+            modules: modules.iter().map(|m| m.to_string()).collect(),
+            download_url: String::new(),
+            version: String::new(),
+            game_version: String::new(),
+            download_count: String::new(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            install_reason: InstallReason::Explicit,
+            install_date: String::new(),
+        }
+    }
+
+    fn dependency(id: &str, dependencies: &[&str]) -> Addon {
+        Addon {
+            install_reason: InstallReason::Dependency,
+            ..addon(id, &[], dependencies)
+        }
+    }
+
+    fn addon_dir(folders: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in folders.iter() {
+            fs::create_dir(dir.path().join(folder)).unwrap();
+        }
+        AddonManager::init_addon_db(dir.path().to_str().unwrap()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn delete_keeps_folders_shared_with_other_addons() {
+        let dir = addon_dir(&["A", "B", "Lib"]);
+        let path = dir.path().to_str().unwrap();
+        let a = addon("1", &["A", "Lib"], &[]);
+        AddonManager::add_to_db(path, a.clone()).unwrap();
+        AddonManager::add_to_db(path, addon("2", &["B", "Lib"], &[])).unwrap();
+
+        AddonManager::delete(path, &a).unwrap();
+        assert!(!dir.path().join("A").exists());
+        assert!(dir.path().join("B").exists());
+        assert!(dir.path().join("Lib").exists());
+        let addons = AddonManager::load_addon_db(path).unwrap();
+        assert_eq!(addons.folders["Lib"], vec!["2".to_string()]);
+
+        let b = addons.addons[0].clone();
+        AddonManager::delete(path, &b).unwrap();
+        assert!(!dir.path().join("B").exists());
+        assert!(!dir.path().join("Lib").exists());
+    }
+
+    #[test]
+    fn legacy_db_rebuilds_the_folder_index() {
+        let dir = addon_dir(&["A", "B", "Lib"]);
+        let path = dir.path().to_str().unwrap();
+        let a = addon("1", &["A", "Lib"], &[]);
+        let legacy = serde_json::json!({
+            "addons": [a, addon("2", &["B", "Lib"], &[])],
+        });
+        fs::write(dir.path().join(FILE_NAME), legacy.to_string()).unwrap();
+
+        let addons = AddonManager::load_addon_db(path).unwrap();
+        assert_eq!(addons.folders["A"], vec!["1".to_string()]);
+        assert_eq!(
+            addons.folders["Lib"],
+            vec!["1".to_string(), "2".to_string()]
+        );
+        AddonManager::delete(path, &a).unwrap();
+        assert!(!dir.path().join("A").exists());
+        assert!(dir.path().join("Lib").exists());
+    }
+
+    #[test]
+    fn delete_skips_missing_folders() {
+        let dir = addon_dir(&["A"]);
+        let path = dir.path().to_str().unwrap();
+        let a = addon("1", &["A", "Missing"], &[]);
+        AddonManager::add_to_db(path, a.clone()).unwrap();
+
+        AddonManager::delete(path, &a).unwrap();
+        assert!(!dir.path().join("A").exists());
+        let addons = AddonManager::load_addon_db(path).unwrap();
+        assert!(addons.addons.is_empty());
+        assert!(addons.folders.is_empty());
+    }
+
+    #[test]
+    fn find_orphans_includes_dependencies_of_orphans() {
+        let dir = addon_dir(&[]);
+        let path = dir.path().to_str().unwrap();
+        for addon in [
+            addon("1", &[], &["2"]),
+            dependency("2", &[]),
+            dependency("3", &["4"]),
+            dependency("4", &[]),
+        ]
+        .iter()
+        {
+            AddonManager::add_to_db(path, addon.clone()).unwrap();
+        }
+
+        let mut orphans: Vec<String> = AddonManager::find_orphans(path)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.addon_id)
+            .collect();
+        orphans.sort();
+        assert_eq!(orphans, vec!["3".to_string(), "4".to_string()]);
+    }
 }
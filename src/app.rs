@@ -1,18 +1,75 @@
-use crate::addon_manager::{Addon, AddonManager, Addons};
-use crate::curse::CurseForgeAPI;
+use crate::action::{Action, ACTIONS, GROUPS};
+use crate::addon_manager::{Addon, AddonManager, Addons, InstallReason};
+use crate::curse::{
+    AddonDetails, Category, CurseForgeAPI, SearchQuery, SearchSort,
+};
+use crate::detect::{Installation, InstallationDetector};
+use crate::input::LineInput;
+use crate::logger;
+use crate::operations::{self, Operation, OperationKind};
+use crate::palette::{CommandPalette, PaletteResult};
+use crate::picker::{fuzzy_score, Picker, PickerResult};
+use crate::search_history::SearchHistory;
+use crate::settings::{Flavor, KeyMatch, SettingField, Settings};
+use crate::setup::{SetupResult, SetupWizard};
+use log::Level;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::time::{Duration, Instant};
+use termion::event::{Key, MouseButton, MouseEvent};
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Style};
 use tui::widgets::{
     Block, Borders, Clear, Paragraph, Row, Table, TableState, Tabs, Text,
 };
 use tui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+const TAB_TITLES: [&str; 4] = ["Installed", "Search", "History", "Settings"];
+
+/// Smaller terminals only show a note instead of the app.
+const MIN_WIDTH: u16 = 30;
+const MIN_HEIGHT: u16 = 10;
+/// Terminals lower than this show the log collapsed to a single line.
+const MIN_HEIGHT_FOR_LOG: u16 = 20;
+const MIN_LOG_HEIGHT: u16 = 5;
+
+const INSTALLED_COLUMNS: [Column; 5] = [
+    Column::new("Status", 15, 0),
+    Column::new("Name", 35, 0),
+    Column::new("WoW", 10, 80),
+    Column::new("Installed", 20, 100),
+    Column::new("Available", 20, 50),
+];
+const SEARCH_COLUMNS: [Column; 5] = [
+    Column::new("Name", 40, 0),
+    Column::new("Status", 10, 0),
+    Column::new("Game Version", 15, 90),
+    Column::new("Date", 15, 70),
+    Column::new("Downloads", 20, 50),
+];
+const HISTORY_COLUMNS: [Column; 7] = [
+    Column::new("Time", 18, 0),
+    Column::new("User", 10, 110),
+    Column::new("Flavor", 10, 70),
+    Column::new("Action", 10, 0),
+    Column::new("Addon", 22, 0),
+    Column::new("Version", 18, 90),
+    Column::new("Result", 12, 50),
+];
+const SETTINGS_COLUMNS: [Column; 3] = [
+    Column::new("Section", 15, 60),
+    Column::new("Setting", 25, 0),
+    Column::new("Value", 60, 0),
+];
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum Tab {
     Installed = 0,
     Search = 1,
+    History = 2,
+    Settings = 3,
 }
 
 impl Tab {
@@ -20,21 +77,78 @@ impl Tab {
         match i {
             0 => Some(Tab::Installed),
             1 => Some(Tab::Search),
+            2 => Some(Tab::History),
+            3 => Some(Tab::Settings),
             _ => None,
         }
     }
 
     pub fn len() -> usize {
-        2
+        4
     }
 }
 
-#[derive(Copy, Clone)]
-pub enum Version {
-    Classic = 0,
-    Tbc = 1,
-    Retail = 2,
-}
+/// How long to wait for the next key of a sequence when the keys pressed so
+/// far are already bound to an action.
+const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Keys that are fixed in modes other than normal mode, listed in the help.
+const MODE_KEYS: [(&str, &[(&str, &str)]); 9] = [
+    (
+        "Search input",
+        &[
+            ("enter", "Search"),
+            ("left, right, home, end", "Move the cursor"),
+            ("ctrl-w", "Delete the previous word"),
+            ("ctrl-u", "Delete to the start"),
+            ("up, down", "Previous searches"),
+            ("esc", "Cancel"),
+        ],
+    ),
+    ("Saving a search", &[("enter", "Save"), ("esc", "Cancel")]),
+    (
+        "Filter input (installed and history tab)",
+        &[("enter", "Keep the filter"), ("esc", "Clear the filter")],
+    ),
+    ("Editing a setting", &[("enter", "Save"), ("esc", "Cancel")]),
+    ("Dialogs", &[("y", "Confirm"), ("any other key", "Close")]),
+    (
+        "Command palette",
+        &[
+            ("up, down", "Select an action"),
+            ("enter", "Run the action"),
+            ("esc", "Close"),
+        ],
+    ),
+    (
+        "Categories and saved searches",
+        &[
+            ("up, down", "Select an entry"),
+            ("enter", "Pick the entry"),
+            ("esc", "Close"),
+        ],
+    ),
+    (
+        "Help, details and changelogs",
+        &[
+            ("up, down", "Scroll"),
+            ("pageup, pagedown", "Scroll faster"),
+            ("any other key", "Close"),
+        ],
+    ),
+    (
+        "Log file",
+        &[
+            ("left, right", "Change the lowest level shown"),
+            ("up, down", "Scroll"),
+            ("any other key", "Close"),
+        ],
+    ),
+];
+
+/// The levels the log file viewer can be filtered by, most severe first.
+const LOG_LEVELS: [Level; 4] =
+    [Level::Error, Level::Warn, Level::Info, Level::Debug];
 
 pub enum LogLevel {
     Info,
@@ -46,27 +160,125 @@ pub enum Mode {
     Normal,
     Editing,
     Dialog,
+    Setup,
+    EditingSetting,
+    Filtering,
+    Palette,
+    Popup,
+    Picker,
+    SavingSearch,
 }
 
+#[derive(Clone)]
 pub struct TableItem {
     pub cells: Vec<String>,
     pub download_url: String,
     pub addon: Addon,
 }
 
+/// The column the installed tab is sorted by. `None` keeps the order of
+/// `.addons.json`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    None,
+    Name,
+    Status,
+    InstallDate,
+    GameVersion,
+}
+
+impl SortColumn {
+    fn next(self) -> SortColumn {
+        match self {
+            SortColumn::None => SortColumn::Name,
+            SortColumn::Name => SortColumn::Status,
+            SortColumn::Status => SortColumn::InstallDate,
+            SortColumn::InstallDate => SortColumn::GameVersion,
+            SortColumn::GameVersion => SortColumn::None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SortColumn::None => "install order",
+            SortColumn::Name => "name",
+            SortColumn::Status => "status",
+            SortColumn::InstallDate => "install date",
+            SortColumn::GameVersion => "WoW version",
+        }
+    }
+}
+
+/// A table column. Columns with a `min_width` are hidden in narrower tables.
+struct Column {
+    header: &'static str,
+    percent: u16,
+    min_width: u16,
+}
+
+impl Column {
+    const fn new(header: &'static str, percent: u16, min_width: u16) -> Column {
+        Column {
+            header,
+            percent,
+            min_width,
+        }
+    }
+}
+
+/// The columns of a table that fit into its width. The hidden columns'
+/// share of the width goes to the visible ones.
+struct VisibleColumns {
+    indices: Vec<usize>,
+    headers: Vec<&'static str>,
+    widths: Vec<Constraint>,
+}
+
+impl VisibleColumns {
+    fn new(columns: &[Column], width: u16) -> VisibleColumns {
+        let indices: Vec<usize> = (0..columns.len())
+            .filter(|&i| width >= columns[i].min_width)
+            .collect();
+        let total: u16 = indices.iter().map(|&i| columns[i].percent).sum();
+        VisibleColumns {
+            headers: indices.iter().map(|&i| columns[i].header).collect(),
+            widths: indices
+                .iter()
+                .map(|&i| {
+                    Constraint::Percentage(columns[i].percent * 100 / total)
+                })
+                .collect(),
+            indices,
+        }
+    }
+
+    fn cells(&self, cells: &[String]) -> Vec<String> {
+        self.indices.iter().map(|&i| cells[i].clone()).collect()
+    }
+}
+
 pub struct StatefulTable {
     state: TableState,
+    /// The first visible row, see `scroll_offset`.
+    offset: usize,
     items: Vec<TableItem>,
+    /// Addon ids of the marked rows, in the order they were marked.
+    marked: Vec<String>,
 }
 
 impl StatefulTable {
     fn new() -> StatefulTable {
         StatefulTable {
             state: TableState::default(),
+            offset: 0,
             items: Vec::new(),
+            marked: Vec::new(),
         }
     }
     pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -81,6 +293,9 @@ impl StatefulTable {
     }
 
     pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -94,67 +309,225 @@ impl StatefulTable {
         self.state.select(Some(i));
     }
 
+    pub fn first(&mut self) {
+        if !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn last(&mut self) {
+        if !self.items.is_empty() {
+            self.state.select(Some(self.items.len() - 1));
+        }
+    }
+
     pub fn get_selected(&self) -> Option<&TableItem> {
         match self.state.selected() {
             Some(i) => self.items.get(i),
             None => None,
         }
     }
-}
 
-pub struct Theme;
+    /// Selects the `row`th visible row.
+    fn select_row(&mut self, row: usize) {
+        let index = self.offset + row;
+        if index < self.items.len() {
+            self.state.select(Some(index));
+        }
+    }
 
-impl Theme {
-    pub fn default() -> Style {
-        Style::default().fg(Color::Gray)
+    pub fn toggle_mark(&mut self) {
+        let id = match self.get_selected() {
+            Some(item) => item.addon.addon_id.clone(),
+            None => return,
+        };
+        match self.marked.iter().position(|m| *m == id) {
+            Some(index) => {
+                self.marked.remove(index);
+            }
+            None => self.marked.push(id),
+        }
     }
 
-    pub fn hover() -> Style {
-        Style::default().fg(Color::Magenta)
+    /// Returns the marked rows that are currently shown.
+    pub fn marked_items(&self) -> Vec<TableItem> {
+        self.items
+            .iter()
+            .filter(|i| self.marked.contains(&i.addon.addon_id))
+            .cloned()
+            .collect()
     }
 
-    pub fn active() -> Style {
-        Style::default().fg(Color::LightCyan)
+    /// Returns the cells of all rows with marked rows flagged in the first
+    /// column.
+    pub fn cells(&self) -> Vec<Vec<String>> {
+        self.items
+            .iter()
+            .map(|item| {
+                let mut cells = item.cells.clone();
+                let flag = if self.marked.contains(&item.addon.addon_id) {
+                    "* "
+                } else {
+                    "  "
+                };
+                cells[0] = format!("{}{}", flag, cells[0]);
+                cells
+            })
+            .collect()
     }
 }
+
+/// A scrollable popup with read-only text like the help or addon details.
+pub struct Popup {
+    title: String,
+    /// Lines of text; headings are drawn in the active style.
+    lines: Vec<(String, bool)>,
+    scroll: u16,
+    /// The lowest level shown if this popup shows the log file.
+    log_level: Option<Level>,
+}
+
 pub struct Dialog {
     text: String,
-    confirmation: bool,
+    /// Runs when the dialog is confirmed. Dialogs without a callback are
+    /// only informational.
+    callback: Option<fn(&mut App)>,
+}
+
+/// Screen areas of the last frame that react to the mouse.
+#[derive(Default)]
+struct ClickAreas {
+    tabs: Rect,
+    flavors: Rect,
+    /// The rows of the table in the selected tab, without its header.
+    rows: Rect,
+    log: Rect,
+    /// The buttons of the dialog and whether they confirm it.
+    buttons: Vec<(Rect, bool)>,
 }
 
+/// Called with the index of the entry picked from a list.
+type PickCallback = fn(&mut App, usize);
+
 pub struct App {
     pub mode: Mode,
-    pub user_input: String,
+    user_input: LineInput,
     pub tab_index: Tab,
     search_table: StatefulTable,
+    /// The search the results in `search_table` belong to.
+    search_query: String,
+    /// Whether the search tab was filled since the start. Until then it
+    /// browses all addons when it is shown.
+    search_loaded: bool,
+    search_sort: SearchSort,
+    search_category: Option<Category>,
+    /// Whether scrolling past the last result loads another page.
+    search_more: bool,
+    /// Fetched when the category list is opened for the first time.
+    categories: Vec<Category>,
+    search_history: SearchHistory,
+    /// The position in the recent searches while browsing them in the
+    /// search input.
+    history_index: Option<usize>,
+    /// The name a search is saved as.
+    pub search_name: String,
+    /// A list to pick from and the function called with the picked index.
+    picker: Option<(Picker, PickCallback)>,
     installed_table: StatefulTable,
-    selected_version: Version,
+    /// All installed addons; `installed_table` shows them filtered and
+    /// sorted.
+    installed_addons: Vec<TableItem>,
+    installed_sort: SortColumn,
+    /// File ids of the addons installed for the flavor by addon id, used to
+    /// flag installed search results.
+    installed_files: HashMap<String, String>,
+    installed_sort_reverse: bool,
+    installed_filter: String,
+    /// The operation history, newest first.
+    operations: Vec<Operation>,
+    operations_filter: String,
+    operations_state: TableState,
+    operations_offset: usize,
+    pub settings: Settings,
+    selected_flavor: usize,
     log_scroll: u16,
+    /// Whether the log only shows the newest message.
+    log_collapsed: bool,
     log_messages: Vec<(String, LogLevel)>,
-    classic_path: String,
-    retail_path: String,
-    tbc_path: String,
     updates: Vec<Addon>,
     dialog: Option<Dialog>,
+    detected_installations: Vec<Installation>,
+    /// The setup wizard and the flavors to restore if it's cancelled.
+    setup: Option<(SetupWizard, Vec<Flavor>)>,
+    palette: Option<CommandPalette>,
+    popup: Option<Popup>,
+    settings_state: TableState,
+    settings_offset: usize,
+    areas: ClickAreas,
+    pub setting_input: String,
+    pending_keys: Vec<Key>,
+    pending_since: Option<Instant>,
+    pub should_quit: bool,
+    /// Where the terminal cursor is shown, set while drawing a focused text
+    /// input.
+    pub cursor: Option<(u16, u16)>,
 }
 
 impl App {
-    pub fn new(classic_path: String, retail_path: String, tbc_path: String) -> App {
+    pub fn new(settings: Settings) -> App {
         let mut app = App {
             mode: Mode::Normal,
-            user_input: String::new(),
+            user_input: LineInput::default(),
             tab_index: Tab::Installed,
-            selected_version: Version::Classic,
+            settings,
+            selected_flavor: 0,
             search_table: StatefulTable::new(),
+            search_query: String::new(),
+            search_loaded: false,
+            search_sort: SearchSort::Popularity,
+            search_category: None,
+            search_more: false,
+            categories: Vec::new(),
+            search_history: SearchHistory::default(),
+            history_index: None,
+            search_name: String::new(),
+            picker: None,
             installed_table: StatefulTable::new(),
+            installed_addons: Vec::new(),
+            installed_sort: SortColumn::None,
+            installed_files: HashMap::new(),
+            installed_sort_reverse: false,
+            installed_filter: String::new(),
+            operations: Vec::new(),
+            operations_filter: String::new(),
+            operations_state: TableState::default(),
+            operations_offset: 0,
             log_scroll: 0,
+            log_collapsed: false,
             log_messages: Vec::new(),
-            classic_path: classic_path,
-            retail_path: retail_path,
-            tbc_path: tbc_path,
             updates: Vec::new(),
             dialog: None,
+            detected_installations: Vec::new(),
+            setup: None,
+            palette: None,
+            popup: None,
+            settings_state: TableState::default(),
+            settings_offset: 0,
+            areas: ClickAreas::default(),
+            setting_input: String::new(),
+            pending_keys: Vec::new(),
+            pending_since: None,
+            should_quit: false,
+            cursor: None,
         };
+        match SearchHistory::load() {
+            Ok(history) => app.search_history = history,
+            Err(err) => app.log(
+                format!("Couldn't load the search history.\n{}\n", err),
+                LogLevel::Warning,
+            ),
+        }
+        app.select_configured_flavor();
         app.load_installed_addons();
         app
     }
@@ -163,33 +536,73 @@ impl App {
     where
         B: Backend,
     {
+        self.cursor = None;
+        self.areas.buttons.clear();
+        if let Some((setup, _)) = &self.setup {
+            setup.draw(
+                frame,
+                frame.size(),
+                &self.settings.flavors,
+                &self.settings.key_bindings,
+                &self.settings.theme,
+            );
+            return;
+        }
+        let size = frame.size();
+        if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+            let text = [Text::raw(format!(
+                "The terminal is too small, it needs at least {}x{} cells.",
+                MIN_WIDTH, MIN_HEIGHT
+            ))];
+            let paragraph = Paragraph::new(text.iter())
+                .style(self.settings.theme.default)
+                .wrap(true);
+            frame.render_widget(paragraph, size);
+            return;
+        }
+        let log_height =
+            if self.log_collapsed || size.height < MIN_HEIGHT_FOR_LOG {
+                1
+            } else {
+                (size.height / 6).max(MIN_LOG_HEIGHT)
+            };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
                 [
-                    Constraint::Percentage(10),
-                    Constraint::Percentage(80),
-                    Constraint::Percentage(10),
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(log_height),
                 ]
                 .as_ref(),
             )
-            .split(frame.size());
+            .split(size);
         self.draw_header(frame, chunks[0]);
         match self.tab_index {
             Tab::Search => self.draw_search_tab(frame, chunks[1]),
             Tab::Installed => self.draw_installed_tab(frame, chunks[1]),
+            Tab::History => self.draw_history_tab(frame, chunks[1]),
+            Tab::Settings => self.draw_settings_tab(frame, chunks[1]),
         };
         self.draw_footer(frame, chunks[2]);
-        match &&self.dialog {
-            Some(_) => self.draw_dialog(frame),
-            None => (),
+        if self.dialog.is_some() {
+            self.draw_dialog(frame);
+        }
+        let area = self.centered_rect(60, 50, frame.size());
+        if let Some(palette) = self.palette.as_mut() {
+            palette.draw(frame, area, &self.settings.theme);
         }
+        if let Some((picker, _)) = self.picker.as_mut() {
+            picker.draw(frame, area, &self.settings.theme);
+        }
+        self.draw_popup(frame);
     }
 
-    pub fn draw_header<B>(&self, frame: &mut Frame<B>, area: Rect)
+    pub fn draw_header<B>(&mut self, frame: &mut Frame<B>, area: Rect)
     where
         B: Backend,
     {
+        let theme = self.settings.theme;
         let tab_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(
@@ -197,21 +610,27 @@ impl App {
                     .as_ref(),
             )
             .split(area);
-        let tab_index = vec!["Installed", "Search"];
+        self.areas.tabs = tab_chunks[0];
+        self.areas.flavors = tab_chunks[1];
         let tabs = Tabs::default()
             .block(Block::default().borders(Borders::ALL).title("Tabs"))
-            .titles(&tab_index)
+            .titles(&TAB_TITLES)
             .select(self.tab_index as usize)
-            .style(Theme::default())
-            .highlight_style(Theme::active());
+            .style(theme.default)
+            .highlight_style(theme.active);
         frame.render_widget(tabs, tab_chunks[0]);
-        let version_index = vec!["Classic", "Tbc", "Retail"];
+        let flavor_index: Vec<&str> = self
+            .settings
+            .flavors
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
         let versions = Tabs::default()
             .block(Block::default().borders(Borders::ALL).title("Version"))
-            .titles(&version_index)
-            .select(self.selected_version as usize)
-            .style(Theme::default())
-            .highlight_style(Theme::active());
+            .titles(&flavor_index)
+            .select(self.selected_flavor)
+            .style(theme.default)
+            .highlight_style(theme.active);
         frame.render_widget(versions, tab_chunks[1]);
     }
 
@@ -219,25 +638,72 @@ impl App {
     where
         B: Backend,
     {
-        let header = ["Status", "Name", "WoW", "Installed", "Available"];
-        let rows = self
+        let theme = self.settings.theme;
+        let area = if self.installed_filter.is_empty()
+            && !matches!(self.mode, Mode::Filtering)
+        {
+            area
+        } else {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [Constraint::Length(3), Constraint::Min(0)].as_ref(),
+                )
+                .split(area);
+            let text = [Text::raw(&self.installed_filter)];
+            let input = Paragraph::new(text.iter()).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(match self.mode {
+                        Mode::Filtering => theme.active,
+                        _ => theme.default,
+                    })
+                    .title("Filter"),
+            );
+            frame.render_widget(input, chunks[0]);
+            chunks[1]
+        };
+        let title = format!(
+            "Addons ({}/{}, sorted by {}{})",
+            self.installed_table.items.len(),
+            self.installed_addons.len(),
+            self.installed_sort.name(),
+            if self.installed_sort_reverse {
+                ", reversed"
+            } else {
+                ""
+            }
+        );
+        let columns = VisibleColumns::new(&INSTALLED_COLUMNS, area.width);
+        let cells: Vec<Vec<String>> = self
             .installed_table
-            .items
+            .cells()
             .iter()
-            .map(|i| Row::StyledData(i.cells.iter(), Theme::default()));
-        let table = Table::new(header.iter(), rows)
-            .block(Block::default().title("Addons").borders(Borders::ALL))
-            .header_style(Theme::active())
-            .widths(&[
-                Constraint::Percentage(15),
-                Constraint::Percentage(35),
-                Constraint::Percentage(10),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-            ])
-            .style(Theme::default())
-            .highlight_style(Theme::active())
+            .map(|cells| columns.cells(cells))
+            .collect();
+        let rows = self.installed_table.items.iter().zip(cells.iter()).map(
+            |(i, cells)| {
+                let style = if i.cells[0] == "Outdated" {
+                    theme.outdated
+                } else {
+                    theme.up_to_date
+                };
+                Row::StyledData(cells.iter(), style)
+            },
+        );
+        let table = Table::new(columns.headers.iter(), rows)
+            .block(Block::default().title(&title).borders(Borders::ALL))
+            .header_style(theme.active)
+            .widths(&columns.widths)
+            .style(theme.default)
+            .highlight_style(theme.hover)
             .highlight_symbol("> ");
+        self.areas.rows = table_rows(area);
+        self.installed_table.offset = scroll_offset(
+            self.installed_table.offset,
+            self.installed_table.state.selected(),
+            self.areas.rows.height,
+        );
         frame.render_stateful_widget(
             table,
             area,
@@ -246,14 +712,12 @@ impl App {
     }
 
     fn load_installed_addons(&mut self) {
-        self.installed_table.items.clear();
+        self.installed_addons.clear();
         self.updates.clear();
-        let path = match self.selected_version {
-            Version::Classic => &self.classic_path,
-            Version::Retail => &self.retail_path,
-            Version::Tbc => &self.tbc_path,
-        };
+        let path = self.get_save_path();
+        // A flavor without a path has nothing installed yet.
         let addons = match AddonManager::load_addon_db(&path) {
+            _ if path.is_empty() => Addons::default(),
             Ok(a) => {
                 self.log(
                     format!("Found {} installed addons.\n", a.addons.len()),
@@ -266,9 +730,14 @@ impl App {
                     format!("Couldn't parse addons.\n{}\n", err),
                     LogLevel::Error,
                 );
-                Addons { addons: Vec::new() }
+                Addons::default()
             }
         };
+        self.installed_files = addons
+            .addons
+            .iter()
+            .map(|a| (a.addon_id.clone(), a.file_id.clone()))
+            .collect();
         let addon_ids: Vec<i32> = addons
             .addons
             .iter()
@@ -277,38 +746,42 @@ impl App {
             .collect();
         let mut updates: HashMap<String, Addon> = HashMap::new();
         if !addon_ids.is_empty() {
-            updates = CurseForgeAPI::check_for_updates(
+            match CurseForgeAPI::check_for_updates(
                 addon_ids,
-                self.selected_version,
-            )
-            .unwrap();
+                &self.get_curse_flavor(),
+                &self.settings.network,
+            ) {
+                Ok(u) => updates = u,
+                Err(err) => self.log(
+                    format!("Couldn't check for updates.\n{}\n", err),
+                    LogLevel::Error,
+                ),
+            }
         }
         for addon in addons.addons.iter() {
             let download_url;
             let latest_version;
-            let status;
             match updates.get(&addon.addon_id) {
-                Some(update) => {
-                    if update.file_id > addon.file_id {
-                        download_url = update.download_url.clone();
-                        latest_version = update.version.clone();
-                        self.updates.push(update.clone());
-                    } else {
-                        download_url = addon.download_url.clone();
-                        latest_version = addon.version.clone();
-                    }
+                Some(update)
+                    if is_newer_file(&update.file_id, &addon.file_id) =>
+                {
+                    download_url = update.download_url.clone();
+                    latest_version = update.version.clone();
+                    let mut update = update.clone();
+                    update.install_reason = addon.install_reason;
+                    self.updates.push(update);
                 }
-                None => {
+                _ => {
                     download_url = addon.download_url.clone();
                     latest_version = addon.version.clone();
                 }
             }
-            if latest_version == addon.version {
-                status = "Up-to-date";
+            let status = if latest_version == addon.version {
+                "Up-to-date"
             } else {
-                status = "Outdated";
-            }
-            self.installed_table.items.push(TableItem {
+                "Outdated"
+            };
+            self.installed_addons.push(TableItem {
                 cells: vec![
                     status.to_string(),
                     addon.name.clone(),
@@ -316,52 +789,451 @@ impl App {
                     addon.version.clone(),
                     latest_version.clone(),
                 ],
-                download_url: download_url,
+                download_url,
                 addon: addon.clone(),
             })
         }
+        self.apply_installed_view();
     }
 
-    fn draw_search_tab<B>(&mut self, frame: &mut Frame<B>, area: Rect)
+    /// Fills the installed table with the installed addons matching the
+    /// filter, in the selected sort order. Without a sort column, filtered
+    /// rows are ordered by how well they match.
+    fn apply_installed_view(&mut self) {
+        let selected = self
+            .installed_table
+            .get_selected()
+            .map(|i| i.addon.addon_id.clone());
+        let filter = &self.installed_filter;
+        let mut items: Vec<(i32, TableItem)> = self
+            .installed_addons
+            .iter()
+            .filter_map(|item| {
+                let score = fuzzy_score(filter, &item.addon.name)?;
+                Some((score, item.clone()))
+            })
+            .collect();
+        match self.installed_sort {
+            SortColumn::None => items.sort_by_key(|(score, _)| -score),
+            SortColumn::Name => {
+                items.sort_by_key(|(_, item)| item.addon.name.to_lowercase())
+            }
+            SortColumn::Status => {
+                items.sort_by(|(_, a), (_, b)| a.cells[0].cmp(&b.cells[0]))
+            }
+            SortColumn::InstallDate => items.sort_by(|(_, a), (_, b)| {
+                b.addon.install_date.cmp(&a.addon.install_date)
+            }),
+            SortColumn::GameVersion => items.sort_by(|(_, a), (_, b)| {
+                version_parts(&b.addon.game_version)
+                    .cmp(&version_parts(&a.addon.game_version))
+            }),
+        }
+        if self.installed_sort_reverse {
+            items.reverse();
+        }
+        self.installed_table.items =
+            items.into_iter().map(|(_, item)| item).collect();
+        let index = selected.and_then(|id| {
+            self.installed_table
+                .items
+                .iter()
+                .position(|i| i.addon.addon_id == id)
+        });
+        self.installed_table.state.select(index);
+    }
+
+    pub fn cycle_installed_sort(&mut self) {
+        self.installed_sort = self.installed_sort.next();
+        self.installed_sort_reverse = false;
+        self.apply_installed_view();
+    }
+
+    pub fn reverse_installed_sort(&mut self) {
+        self.installed_sort_reverse = !self.installed_sort_reverse;
+        self.apply_installed_view();
+    }
+
+    pub fn filter_installed(&mut self) {
+        if self.tab_index != Tab::Installed {
+            self.tab_index = Tab::Installed;
+            self.load_installed_addons();
+        }
+        self.mode = Mode::Filtering;
+    }
+
+    pub fn filter_history(&mut self) {
+        if self.tab_index != Tab::History {
+            self.tab_index = Tab::History;
+            self.load_operations();
+        }
+        self.mode = Mode::Filtering;
+    }
+
+    /// Edits the filter of the installed or history tab. Enter keeps the
+    /// filter, Esc clears it.
+    pub fn handle_filter_key(&mut self, key: Key) {
+        let filter = match self.tab_index {
+            Tab::History => &mut self.operations_filter,
+            _ => &mut self.installed_filter,
+        };
+        match key {
+            Key::Char('\n') => self.mode = Mode::Normal,
+            Key::Char(c) => filter.push(c),
+            Key::Backspace => {
+                filter.pop();
+            }
+            Key::Esc => {
+                filter.clear();
+                self.mode = Mode::Normal;
+            }
+            _ => return,
+        }
+        match self.tab_index {
+            Tab::History => self.select_first_operation(),
+            _ => self.apply_installed_view(),
+        }
+    }
+
+    /// Loads the operation history for the history tab.
+    fn load_operations(&mut self) {
+        match operations::load() {
+            Ok(mut loaded) => {
+                loaded.reverse();
+                self.operations = loaded;
+            }
+            Err(err) => self.log(
+                format!("Couldn't load the operation history.\n{}\n", err),
+                LogLevel::Error,
+            ),
+        }
+        self.select_first_operation();
+    }
+
+    /// The operations matching the filter of the history tab.
+    fn filtered_operations(&self) -> Vec<&Operation> {
+        self.operations
+            .iter()
+            .filter(|o| o.matches(&self.operations_filter))
+            .collect()
+    }
+
+    fn select_first_operation(&mut self) {
+        let empty = self.filtered_operations().is_empty();
+        self.operations_state
+            .select(if empty { None } else { Some(0) });
+        self.operations_offset = 0;
+    }
+
+    /// Appends an operation on `addon` in the selected flavor to the
+    /// operation history.
+    fn record(
+        &mut self,
+        kind: OperationKind,
+        addon: &Addon,
+        versions: (Option<&str>, Option<&str>),
+        result: &Result<(), Box<dyn Error>>,
+    ) {
+        let mut operation = Operation::new(
+            kind,
+            &self.flavor_name(),
+            &addon.addon_id,
+            &addon.name,
+        );
+        operation.from_version = versions.0.map(str::to_string);
+        operation.to_version = versions.1.map(str::to_string);
+        operation.error = result
+            .as_ref()
+            .err()
+            .map(|err| err.to_string().replace('\n', " "));
+        if let Err(err) = operations::append(&operation) {
+            self.log(
+                format!("Couldn't write the operation history.\n{}\n", err),
+                LogLevel::Warning,
+            );
+        }
+    }
+
+    fn draw_history_tab<B>(&mut self, frame: &mut Frame<B>, area: Rect)
     where
         B: Backend,
     {
+        let theme = self.settings.theme;
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(
-                [Constraint::Percentage(10), Constraint::Percentage(90)]
-                    .as_ref(),
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(area);
+        let filtering = matches!(self.mode, Mode::Filtering);
+        let text = if filtering || !self.operations_filter.is_empty() {
+            self.operations_filter.clone()
+        } else {
+            format!(
+                "Press {} to filter by addon, flavor, user, action or version.",
+                Settings::sequences_to_string(
+                    self.settings.key_bindings.get(Action::FilterHistory)
+                )
+            )
+        };
+        let text = [Text::raw(text)];
+        let input = Paragraph::new(text.iter()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(if filtering {
+                    theme.active
+                } else {
+                    theme.default
+                })
+                .title("Filter"),
+        );
+        frame.render_widget(input, chunks[0]);
+        let columns = VisibleColumns::new(&HISTORY_COLUMNS, chunks[1].width);
+        let rows: Vec<(Vec<String>, bool)> = self
+            .filtered_operations()
+            .into_iter()
+            .map(|o| {
+                let cells = columns.cells(&[
+                    o.time.clone(),
+                    o.user.clone(),
+                    o.flavor.clone(),
+                    o.kind.name().to_string(),
+                    o.addon.clone(),
+                    o.versions(),
+                    o.result().to_string(),
+                ]);
+                (cells, o.error.is_some())
+            })
+            .collect();
+        let rows = rows.iter().map(|(cells, failed)| {
+            Row::StyledData(
+                cells.iter(),
+                if *failed { theme.error } else { theme.default },
             )
+        });
+        let table = Table::new(columns.headers.iter(), rows)
+            .block(Block::default().title("History").borders(Borders::ALL))
+            .header_style(theme.active)
+            .widths(&columns.widths)
+            .style(theme.default)
+            .highlight_style(theme.hover)
+            .highlight_symbol("> ");
+        self.areas.rows = table_rows(chunks[1]);
+        self.operations_offset = scroll_offset(
+            self.operations_offset,
+            self.operations_state.selected(),
+            self.areas.rows.height,
+        );
+        frame.render_stateful_widget(
+            table,
+            chunks[1],
+            &mut self.operations_state,
+        );
+    }
+
+    fn draw_settings_tab<B>(&mut self, frame: &mut Frame<B>, area: Rect)
+    where
+        B: Backend,
+    {
+        let theme = self.settings.theme;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(area);
+        let entries = self.settings.entries();
+        let selected =
+            self.settings_state.selected().and_then(|i| entries.get(i));
+        let (title, text) = match (&self.mode, selected) {
+            (Mode::EditingSetting, Some(entry)) => {
+                (format!("Edit {}", entry.name), self.setting_input.clone())
+            }
+            _ => (
+                "Edit".to_string(),
+                format!(
+                    "Press {} to edit the selected setting.",
+                    Settings::sequences_to_string(
+                        self.settings.key_bindings.get(Action::EditSetting)
+                    )
+                ),
+            ),
+        };
+        let text = [Text::raw(text)];
+        let input = Paragraph::new(text.iter()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(match self.mode {
+                    Mode::EditingSetting => theme.active,
+                    _ => theme.default,
+                })
+                .title(&title),
+        );
+        frame.render_widget(input, chunks[0]);
+        let columns = VisibleColumns::new(&SETTINGS_COLUMNS, chunks[1].width);
+        let cells: Vec<Vec<String>> = entries
+            .into_iter()
+            .map(|e| columns.cells(&[e.section, e.name, e.value]))
+            .collect();
+        let rows = cells
+            .iter()
+            .map(|c| Row::StyledData(c.iter(), theme.default));
+        let table = Table::new(columns.headers.iter(), rows)
+            .block(Block::default().title("Settings").borders(Borders::ALL))
+            .header_style(theme.active)
+            .widths(&columns.widths)
+            .style(theme.default)
+            .highlight_style(theme.hover)
+            .highlight_symbol("> ");
+        self.areas.rows = table_rows(chunks[1]);
+        self.settings_offset = scroll_offset(
+            self.settings_offset,
+            self.settings_state.selected(),
+            self.areas.rows.height,
+        );
+        frame.render_stateful_widget(
+            table,
+            chunks[1],
+            &mut self.settings_state,
+        );
+    }
+
+    /// Starts editing the selected setting if the settings tab is active.
+    pub fn edit_setting(&mut self) {
+        if self.tab_index != Tab::Settings {
+            return;
+        }
+        let entries = self.settings.entries();
+        if let Some(entry) =
+            self.settings_state.selected().and_then(|i| entries.get(i))
+        {
+            self.setting_input = entry.value.clone();
+            self.mode = Mode::EditingSetting;
+        }
+    }
+
+    /// Validates and applies the edited setting and writes the config file.
+    /// Invalid input is logged and keeps the setting in edit mode.
+    pub fn apply_setting(&mut self) {
+        let entries = self.settings.entries();
+        let entry =
+            match self.settings_state.selected().and_then(|i| entries.get(i)) {
+                Some(entry) => entry,
+                None => {
+                    self.mode = Mode::Normal;
+                    return;
+                }
+            };
+        if let Err(err) = self.settings.set(entry.field, &self.setting_input) {
+            self.log(format!("{}\n", err), LogLevel::Error);
+            return;
+        }
+        self.mode = Mode::Normal;
+        logger::configure(&self.settings.log);
+        if let SettingField::FlavorPath(index) = entry.field {
+            let path = self.settings.flavors[index].path.clone();
+            if !path.is_empty() {
+                if let Err(err) = AddonManager::init_addon_db(&path) {
+                    self.log(
+                        format!(
+                            "Couldn't load addon directory {}.\n{}\n",
+                            path, err
+                        ),
+                        LogLevel::Error,
+                    );
+                }
+            }
+        }
+        match self.settings.save() {
+            Ok(_) => {
+                self.log(format!("{} saved.\n", entry.name), LogLevel::Info)
+            }
+            Err(err) => self.log(
+                format!("Couldn't save config.\n{}\n", err),
+                LogLevel::Error,
+            ),
+        }
+    }
+
+    fn draw_search_tab<B>(&mut self, frame: &mut Frame<B>, area: Rect)
+    where
+        B: Backend,
+    {
+        let theme = self.settings.theme;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
             .split(area);
-        let text = [Text::raw(&self.user_input)];
+        let (title, text) = match self.mode {
+            Mode::SavingSearch => ("Save search as", self.search_name.as_str()),
+            _ => {
+                let width = chunks[0].width.saturating_sub(3);
+                let (text, column) = self.user_input.visible(width);
+                if let Mode::Editing = self.mode {
+                    self.cursor =
+                        Some((chunks[0].x + 1 + column, chunks[0].y + 1));
+                }
+                ("Search", text)
+            }
+        };
+        let text = [Text::raw(text)];
         let input = Paragraph::new(text.iter()).block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(match self.mode {
-                    Mode::Editing => Theme::active(),
-                    _ => Theme::default(),
+                    Mode::Editing | Mode::SavingSearch => theme.active,
+                    _ => theme.default,
                 })
-                .title("Search"),
+                .title(title),
         );
         frame.render_widget(input, chunks[0]);
-        let header = ["Name", "Game Version", "Date", "Downloads"];
-        let rows = self
-            .search_table
-            .items
+        let title = format!(
+            "{} ({}{}, sorted by {}, {})",
+            if self.search_query.is_empty() {
+                "All addons"
+            } else {
+                "Addons"
+            },
+            self.search_table.items.len(),
+            if self.search_more { "+" } else { "" },
+            self.search_sort.name(),
+            self.search_category
+                .as_ref()
+                .map_or("all categories", |c| c.name.as_str())
+        );
+        let columns = VisibleColumns::new(&SEARCH_COLUMNS, chunks[1].width);
+        let mut cells = self.search_table.cells();
+        let mut styles = Vec::new();
+        for (item, cells) in
+            self.search_table.items.iter().zip(cells.iter_mut())
+        {
+            let (status, style) =
+                match self.installed_files.get(&item.addon.addon_id) {
+                    Some(file_id)
+                        if is_newer_file(&item.addon.file_id, file_id) =>
+                    {
+                        ("Update", theme.outdated)
+                    }
+                    Some(_) => ("Installed", theme.up_to_date),
+                    None => ("", theme.default),
+                };
+            cells.insert(1, status.to_string());
+            *cells = columns.cells(cells);
+            styles.push(style);
+        }
+        let rows = cells
             .iter()
-            .map(|i| Row::StyledData(i.cells.iter(), Theme::default()));
-        let table = Table::new(header.iter(), rows)
-            .block(Block::default().title("Addons").borders(Borders::ALL))
-            .header_style(Theme::active())
-            .widths(&[
-                Constraint::Percentage(50),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(20),
-            ])
-            .style(Theme::default())
-            .highlight_style(Theme::active())
+            .zip(styles)
+            .map(|(c, style)| Row::StyledData(c.iter(), style));
+        let table = Table::new(columns.headers.iter(), rows)
+            .block(Block::default().title(&title).borders(Borders::ALL))
+            .header_style(theme.active)
+            .widths(&columns.widths)
+            .style(theme.default)
+            .highlight_style(theme.hover)
             .highlight_symbol("> ");
+        self.areas.rows = table_rows(chunks[1]);
+        self.search_table.offset = scroll_offset(
+            self.search_table.offset,
+            self.search_table.state.selected(),
+            self.areas.rows.height,
+        );
         frame.render_stateful_widget(
             table,
             chunks[1],
@@ -373,6 +1245,7 @@ impl App {
     where
         B: Backend,
     {
+        let theme = self.settings.theme;
         let mut text = self
             .log_messages
             .iter()
@@ -381,15 +1254,15 @@ impl App {
                 let prefix;
                 match log_level {
                     LogLevel::Info => {
-                        style = Style::default();
+                        style = theme.info;
                         prefix = "[Info]";
                     }
                     LogLevel::Warning => {
-                        style = Style::default().fg(Color::Yellow);
+                        style = theme.warning;
                         prefix = "[Warning]";
                     }
                     LogLevel::Error => {
-                        style = Style::default().fg(Color::Red);
+                        style = theme.error;
                         prefix = "[Error]";
                     }
                 };
@@ -397,6 +1270,14 @@ impl App {
             })
             .collect::<Vec<Text>>();
         text.reverse();
+        self.areas.log = area;
+        if area.height < 3 {
+            // Too small for a border, only the newest message is shown.
+            let paragraph =
+                Paragraph::new(text.iter().take(1)).alignment(Alignment::Left);
+            frame.render_widget(paragraph, area);
+            return;
+        }
         let paragraph = Paragraph::new(text.iter())
             .block(Block::default().title("Log").borders(Borders::ALL))
             .alignment(Alignment::Left)
@@ -405,168 +1286,1148 @@ impl App {
         frame.render_widget(paragraph, area);
     }
 
-    pub fn draw_dialog<B>(&mut self, frame: &mut Frame<B>)
-    where
-        B: Backend,
-    {
-        let mut text =
-            vec![Text::raw(self.dialog.as_ref().unwrap().text.clone())];
-        if self.dialog.as_ref().unwrap().confirmation {
-            text.push(Text::raw("\n(Y)es/(N)o"));
+    /// Lists every action with its current keys, grouped by the tab it is
+    /// used in, followed by the fixed keys of the other modes.
+    pub fn open_help(&mut self) {
+        let line = |keys: String, description: String| {
+            (format!("  {:<20}{}", keys, description), false)
+        };
+        let mut lines = Vec::new();
+        for group in GROUPS.iter() {
+            lines.push((group.to_string(), true));
+            for info in ACTIONS.iter().filter(|info| info.group == *group) {
+                let keys = self.settings.key_bindings.get(info.action);
+                let keys = if keys.is_empty() {
+                    "(unbound)".to_string()
+                } else {
+                    Settings::sequences_to_string(keys)
+                };
+                lines.push(line(keys, info.description.to_string()));
+            }
+            if *group == "General" {
+                for (index, flavor) in self.settings.flavors.iter().enumerate()
+                {
+                    lines.push(line(
+                        Settings::key_to_string(flavor.key),
+                        Action::SelectFlavor(index)
+                            .description(&self.settings.flavors),
+                    ));
+                }
+            }
+            lines.push((String::new(), false));
         }
-        let paragraph = Paragraph::new(text.iter())
-            .block(Block::default().borders(Borders::ALL).title("Warning"))
-            .alignment(Alignment::Center);
-        let area = self.centered_rect(50, 10, frame.size());
-        frame.render_widget(Clear, area);
-        frame.render_widget(paragraph, area);
-        self.mode = Mode::Dialog;
+        for (mode, keys) in MODE_KEYS.iter() {
+            lines.push((mode.to_string(), true));
+            for (key, description) in keys.iter() {
+                lines.push(line(key.to_string(), description.to_string()));
+            }
+            lines.push((String::new(), false));
+        }
+        self.open_popup("Help".to_string(), lines);
     }
 
-    pub fn add_dialog(&mut self, text: String, confirmation: bool) {
-        self.dialog = Some(Dialog {
-            text: text,
-            confirmation: confirmation,
+    pub fn open_popup(&mut self, title: String, lines: Vec<(String, bool)>) {
+        self.popup = Some(Popup {
+            title,
+            lines,
+            scroll: 0,
+            log_level: None,
         });
+        self.mode = Mode::Popup;
     }
 
-    pub fn stop_dialog(&mut self) {
-        self.mode = Mode::Normal;
-        self.dialog = None;
+    pub fn show_log(&mut self) {
+        self.open_log_file(Level::Info);
     }
 
-    pub fn search(&mut self, name: String) {
-        let log_level;
-        let msg;
-        match CurseForgeAPI::search(&name, self.selected_version) {
-            Ok(res) => {
-                msg = format!("Found {} addons for {}.\n", res.len(), name);
-                log_level = LogLevel::Info;
-                self.search_table.items = res;
-            }
+    /// Shows the entries of the log file with at least the given level,
+    /// newest first.
+    fn open_log_file(&mut self, level: Level) {
+        let content = match logger::path().and_then(|path| {
+            fs::read_to_string(&path).map_err(|err| {
+                format!("Couldn't read {}.\n{}", path.display(), err).into()
+            })
+        }) {
+            Ok(content) => content,
             Err(err) => {
-                msg = format!(
-                    "Couldn't find any addons for {}.\n{}\n",
-                    name, err
-                );
-                log_level = LogLevel::Error;
+                self.log(format!("{}\n", err), LogLevel::Error);
+                return;
+            }
+        };
+        // Lines without a level continue the entry before them.
+        let mut entries: Vec<(Level, Vec<&str>)> = Vec::new();
+        for line in content.lines() {
+            match (logger::line_level(line), entries.last_mut()) {
+                (Some(level), _) => entries.push((level, vec![line])),
+                (None, Some(entry)) => entry.1.push(line),
+                (None, None) => (),
             }
         }
-        self.log(msg, log_level);
-    }
-
-    pub fn download(&mut self) {
-        if self.tab_index == Tab::Search {
-            let item = self.search_table.get_selected().unwrap();
-            let save_path = self.get_save_path();
-            let log_level;
-            let msg;
-            if let Err(err) =
-                CurseForgeAPI::download(&item.download_url, &save_path)
-                    .and_then(|_| {
-                        AddonManager::add_to_db(&save_path, item.addon.clone())
-                    })
+        let mut lines: Vec<(String, bool)> = entries
+            .iter()
+            .rev()
+            .filter(|(l, _)| *l <= level)
+            .flat_map(|(l, entry)| {
+                let heading = *l <= Level::Warn;
+                entry.iter().map(move |line| (line.to_string(), heading))
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(("No entries.".to_string(), false));
+        }
+        self.popup = Some(Popup {
+            title: format!("Log ({} and above, left/right to change)", level),
+            lines,
+            scroll: 0,
+            log_level: Some(level),
+        });
+        self.mode = Mode::Popup;
+    }
+
+    /// Fetches the project details and latest changelog of the selected
+    /// addon and shows them in a popup.
+    pub fn show_details(&mut self) {
+        let item = match self.tab_index {
+            Tab::Installed => self.installed_table.get_selected(),
+            Tab::Search => self.search_table.get_selected(),
+            Tab::History | Tab::Settings => None,
+        };
+        let addon = match item {
+            Some(item) => item.addon.clone(),
+            None => return,
+        };
+        match CurseForgeAPI::details(
+            &addon.addon_id,
+            &self.get_curse_flavor(),
+            &self.settings.network,
+        ) {
+            Ok(details) => {
+                let lines =
+                    App::details_lines(&details, &addon, self.tab_index);
+                self.open_popup(details.name.clone(), lines);
+            }
+            Err(err) => self.log(
+                format!("Couldn't fetch details of {}.\n{}\n", addon.name, err),
+                LogLevel::Error,
+            ),
+        }
+    }
+
+    /// Shows the changelogs of all files between the installed and the
+    /// available version of every outdated addon.
+    pub fn show_changelogs(&mut self) {
+        if self.updates.is_empty() {
+            self.log(
+                "All addons are up-to-date.\n".to_string(),
+                LogLevel::Info,
+            );
+            return;
+        }
+        let flavor = self.get_curse_flavor();
+        let mut lines = Vec::new();
+        for update in self.updates.clone().iter() {
+            let installed = match self
+                .installed_addons
+                .iter()
+                .find(|i| i.addon.addon_id == update.addon_id)
             {
-                msg =
-                    format!("Couldn't install {}.\n{}\n", &item.cells[0], err);
-                log_level = LogLevel::Error;
-            } else {
-                msg = format!("{} successfully installed.\n", &item.cells[0]);
+                Some(item) => item.addon.clone(),
+                None => continue,
+            };
+            lines.push((
+                format!(
+                    "{}: {} -> {}",
+                    update.name, installed.version, update.version
+                ),
+                true,
+            ));
+            match CurseForgeAPI::changelogs(
+                &update.addon_id,
+                &installed.file_id,
+                &update.file_id,
+                &flavor,
+                &self.settings.network,
+            ) {
+                Ok(changelogs) => {
+                    for changelog in changelogs.iter() {
+                        lines.push((
+                            format!("{} ({})", changelog.name, changelog.date),
+                            false,
+                        ));
+                        lines.extend(
+                            changelog
+                                .text
+                                .lines()
+                                .map(|l| (format!("  {}", l), false)),
+                        );
+                    }
+                }
+                Err(err) => {
+                    lines.push((
+                        "Couldn't fetch changelogs.".to_string(),
+                        false,
+                    ));
+                    self.log(
+                        format!(
+                            "Couldn't fetch changelogs of {}.\n{}\n",
+                            update.name, err
+                        ),
+                        LogLevel::Error,
+                    );
+                }
+            }
+            lines.push((String::new(), false));
+        }
+        self.open_popup("Changelogs".to_string(), lines);
+    }
+
+    fn details_lines(
+        details: &AddonDetails,
+        addon: &Addon,
+        tab: Tab,
+    ) -> Vec<(String, bool)> {
+        let mut lines = vec![
+            (details.summary.clone(), false),
+            (String::new(), false),
+            (
+                format!("Authors:     {}", details.authors.join(", ")),
+                false,
+            ),
+            (
+                format!("Categories:  {}", details.categories.join(", ")),
+                false,
+            ),
+            (format!("Website:     {}", details.website), false),
+            (format!("Downloads:   {}", details.download_count), false),
+        ];
+        if tab == Tab::Installed {
+            lines.push((format!("Installed:   {}", addon.version), false));
+        }
+        if let Some((name, date)) = &details.latest_file {
+            lines.push((format!("Latest file: {} ({})", name, date), false));
+        }
+        lines.push((String::new(), false));
+        lines.push(("Changelog".to_string(), true));
+        if details.changelog.is_empty() {
+            lines.push(("No changelog available.".to_string(), false));
+        }
+        lines.extend(details.changelog.lines().map(|l| (l.to_string(), false)));
+        lines
+    }
+
+    fn draw_popup<B>(&self, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let popup = match &self.popup {
+            Some(popup) => popup,
+            None => return,
+        };
+        let theme = self.settings.theme;
+        let text: Vec<Text> = popup
+            .lines
+            .iter()
+            .map(|(line, heading)| {
+                if *heading {
+                    Text::styled(format!("{}\n", line), theme.active)
+                } else {
+                    Text::raw(format!("{}\n", line))
+                }
+            })
+            .collect();
+        let paragraph = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title(&popup.title))
+            .style(theme.default)
+            .wrap(true)
+            .scroll(popup.scroll);
+        let area = self.centered_rect(70, 80, frame.size());
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn handle_popup_key(&mut self, key: Key) {
+        let popup = match self.popup.as_mut() {
+            Some(popup) => popup,
+            None => return,
+        };
+        let last = popup.lines.len().saturating_sub(1) as u16;
+        if let (Some(level), Key::Left) | (Some(level), Key::Right) =
+            (popup.log_level, key)
+        {
+            let index = LOG_LEVELS.iter().position(|l| *l == level).unwrap();
+            let index = match key {
+                Key::Left => index.saturating_sub(1),
+                _ => (index + 1).min(LOG_LEVELS.len() - 1),
+            };
+            self.open_log_file(LOG_LEVELS[index]);
+            return;
+        }
+        match key {
+            Key::Down => popup.scroll = (popup.scroll + 1).min(last),
+            Key::Up => popup.scroll = popup.scroll.saturating_sub(1),
+            Key::PageDown => popup.scroll = (popup.scroll + 10).min(last),
+            Key::PageUp => popup.scroll = popup.scroll.saturating_sub(10),
+            _ => {
+                self.popup = None;
+                self.mode = Mode::Normal;
+            }
+        }
+    }
+
+    pub fn draw_dialog<B>(&mut self, frame: &mut Frame<B>)
+    where
+        B: Backend,
+    {
+        let dialog = self.dialog.as_ref().unwrap();
+        let text = [Text::raw(dialog.text.clone())];
+        let buttons: &[(&str, bool)] = if dialog.callback.is_some() {
+            &[("[ (Y)es ]", true), ("[ (N)o ]", false)]
+        } else {
+            &[("[ OK ]", false)]
+        };
+        let paragraph = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title("Warning"))
+            .style(self.settings.theme.default)
+            .alignment(Alignment::Center)
+            .wrap(true);
+        let lines = dialog.text.lines().count() as u16 + 4;
+        let percent_y =
+            (lines * 100 / frame.size().height.max(1)).clamp(10, 80);
+        let area = self.centered_rect(50, percent_y, frame.size());
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+
+        // The buttons go on the last line inside the border, centered like
+        // the text.
+        let labels: Vec<&str> =
+            buttons.iter().map(|(label, _)| *label).collect();
+        let line = labels.join("   ");
+        let line_area = Rect::new(
+            area.x + 1,
+            area.bottom().saturating_sub(2),
+            area.width.saturating_sub(2),
+            1,
+        );
+        let mut x = line_area.x
+            + (line_area.width / 2).saturating_sub(line.width() as u16 / 2);
+        for (label, confirm) in buttons.iter() {
+            let width = label.width() as u16;
+            self.areas
+                .buttons
+                .push((Rect::new(x, line_area.y, width, 1), *confirm));
+            x += width + 3;
+        }
+        let line = [Text::raw(line)];
+        let line = Paragraph::new(line.iter())
+            .style(self.settings.theme.default)
+            .alignment(Alignment::Center);
+        frame.render_widget(line, line_area);
+        self.mode = Mode::Dialog;
+    }
+
+    pub fn add_dialog(&mut self, text: String, callback: Option<fn(&mut App)>) {
+        self.dialog = Some(Dialog { text, callback });
+    }
+
+    /// Logs all problems found in the config file and lists them in a
+    /// dialog.
+    pub fn show_config_diagnostics(&mut self, diagnostics: &[String]) {
+        for diagnostic in diagnostics.iter() {
+            self.log(format!("{}\n", diagnostic), LogLevel::Warning);
+        }
+        self.add_dialog(
+            format!(
+                "Found problems in {}:\n\n{}",
+                self.settings.config_path,
+                diagnostics.join("\n")
+            ),
+            None,
+        );
+    }
+
+    /// Runs the callback of the open dialog and closes it.
+    pub fn confirm_dialog(&mut self) {
+        if let Some(callback) = self
+            .dialog
+            .as_mut()
+            .and_then(|dialog| dialog.callback.take())
+        {
+            callback(self);
+        }
+        self.stop_dialog();
+    }
+
+    pub fn stop_dialog(&mut self) {
+        self.mode = Mode::Normal;
+        self.dialog = None;
+    }
+
+    /// Searches for `name`. An empty name lists all addons in the current
+    /// sort order.
+    pub fn search(&mut self, name: String) {
+        let log_level;
+        let msg;
+        self.search_loaded = true;
+        if !name.is_empty() {
+            let flavor = self.flavor_name();
+            self.search_history.add(&flavor, &name);
+            self.save_search_history();
+        }
+        let query = SearchQuery {
+            filter: &name,
+            sort: self.search_sort,
+            category: self.search_category.as_ref(),
+            index: 0,
+        };
+        match CurseForgeAPI::search(
+            &query,
+            &self.get_curse_flavor(),
+            &self.settings.network,
+        ) {
+            Ok(res) => {
+                let count = format!(
+                    "{}{}",
+                    res.items.len(),
+                    if res.more { "+" } else { "" }
+                );
+                msg = if name.is_empty() {
+                    format!(
+                        "Found {} addons sorted by {}.\n",
+                        count,
+                        self.search_sort.name()
+                    )
+                } else {
+                    format!("Found {} addons for {}.\n", count, name)
+                };
                 log_level = LogLevel::Info;
+                self.search_table.items = res.items;
+                self.search_table.marked.clear();
+                self.search_table.state.select(None);
+                self.search_more = res.more;
+                self.search_query = name;
+                self.load_installed_files();
+            }
+            Err(err) => {
+                msg = format!(
+                    "Couldn't find any addons for {}.\n{}\n",
+                    name, err
+                );
+                log_level = LogLevel::Error;
+            }
+        }
+        self.log(msg, log_level);
+    }
+
+    /// Lists all addons of the flavor, sorted by the current search order.
+    pub fn browse(&mut self) {
+        self.tab_index = Tab::Search;
+        self.user_input.clear();
+        self.search(String::new());
+    }
+
+    /// Appends the next page of results to the search table.
+    fn load_more_results(&mut self) {
+        let query = SearchQuery {
+            filter: &self.search_query,
+            sort: self.search_sort,
+            category: self.search_category.as_ref(),
+            index: self.search_table.items.len(),
+        };
+        match CurseForgeAPI::search(
+            &query,
+            &self.get_curse_flavor(),
+            &self.settings.network,
+        ) {
+            Ok(res) => {
+                self.search_more = res.more;
+                self.search_table.items.extend(res.items);
+            }
+            Err(err) => {
+                self.search_more = false;
+                self.log(
+                    format!("Couldn't load more addons.\n{}\n", err),
+                    LogLevel::Error,
+                );
+            }
+        }
+    }
+
+    pub fn cycle_search_sort(&mut self) {
+        if self.tab_index != Tab::Search {
+            return;
+        }
+        self.search_sort = self.search_sort.next();
+        self.search(self.search_query.clone());
+    }
+
+    /// Opens the list of categories. The categories are fetched once.
+    pub fn open_categories(&mut self) {
+        if self.tab_index != Tab::Search {
+            return;
+        }
+        if self.categories.is_empty() {
+            match CurseForgeAPI::categories(&self.settings.network) {
+                Ok(categories) => self.categories = categories,
+                Err(err) => {
+                    self.log(
+                        format!("Couldn't load the categories.\n{}\n", err),
+                        LogLevel::Error,
+                    );
+                    return;
+                }
+            }
+        }
+        let items = std::iter::once("All categories".to_string())
+            .chain(self.categories.iter().map(|c| c.name.clone()))
+            .collect();
+        self.open_picker("Category", items, App::select_category);
+    }
+
+    /// Filters by the category at `index` of the category list, where the
+    /// first entry stands for all categories.
+    fn select_category(&mut self, index: usize) {
+        self.search_category = index
+            .checked_sub(1)
+            .and_then(|i| self.categories.get(i))
+            .cloned();
+        self.search(self.search_query.clone());
+    }
+
+    fn open_picker(
+        &mut self,
+        title: &str,
+        items: Vec<String>,
+        callback: PickCallback,
+    ) {
+        self.picker = Some((Picker::new(title, items), callback));
+        self.mode = Mode::Picker;
+    }
+
+    pub fn handle_picker_key(&mut self, key: Key) {
+        let (picker, callback) = match self.picker.as_mut() {
+            Some(picker) => picker,
+            None => return,
+        };
+        match picker.handle_key(key) {
+            PickerResult::Continue => {}
+            PickerResult::Select(index) => {
+                let callback = *callback;
+                self.picker = None;
+                self.mode = Mode::Normal;
+                callback(self, index);
+            }
+            PickerResult::Cancelled => {
+                self.picker = None;
+                self.mode = Mode::Normal;
+            }
+        }
+    }
+
+    pub fn handle_search_key(&mut self, key: Key) {
+        match key {
+            Key::Char('\n') => {
+                self.mode = Mode::Normal;
+                self.search(self.user_input.text().to_string());
+            }
+            Key::Up => self.select_history(true),
+            Key::Down => self.select_history(false),
+            Key::Esc => self.mode = Mode::Normal,
+            key => {
+                if self.user_input.handle_key(key) {
+                    self.history_index = None;
+                }
+            }
+        }
+    }
+
+    /// Replaces the search input with an older or newer recent search of
+    /// the flavor. Going past the newest one clears the input.
+    fn select_history(&mut self, older: bool) {
+        let recent = self.search_history.recent(&self.flavor_name());
+        let index = match (self.history_index, older) {
+            (None, true) => recent.len().checked_sub(1),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < recent.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.user_input
+            .set(index.map_or(String::new(), |i| recent[i].clone()));
+        self.history_index = index;
+    }
+
+    /// Asks for a name to save the current search as.
+    pub fn save_search(&mut self) {
+        if self.tab_index != Tab::Search {
+            return;
+        }
+        if self.search_query.is_empty() {
+            self.log(
+                "Search for something before saving the search.\n".to_string(),
+                LogLevel::Warning,
+            );
+            return;
+        }
+        self.search_name.clear();
+        self.mode = Mode::SavingSearch;
+    }
+
+    pub fn handle_save_search_key(&mut self, key: Key) {
+        match key {
+            Key::Char('\n') => {
+                self.mode = Mode::Normal;
+                let name = self.search_name.trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+                self.search_history.save_query(&name, &self.search_query);
+                self.save_search_history();
+                self.log(
+                    format!("Saved the search {}.\n", name),
+                    LogLevel::Info,
+                );
+            }
+            Key::Char(c) => self.search_name.push(c),
+            Key::Backspace => {
+                self.search_name.pop();
+            }
+            Key::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    /// Lists the saved searches to run one of them.
+    pub fn open_saved_searches(&mut self) {
+        if self.search_history.saved.is_empty() {
+            self.log(
+                "There are no saved searches yet.\n".to_string(),
+                LogLevel::Info,
+            );
+            return;
+        }
+        let items = self
+            .search_history
+            .saved
+            .iter()
+            .map(|q| format!("{}: {}", q.name, q.query))
+            .collect();
+        self.open_picker("Saved searches", items, App::run_saved_search);
+    }
+
+    fn run_saved_search(&mut self, index: usize) {
+        if let Some(saved) = self.search_history.saved.get(index) {
+            let query = saved.query.clone();
+            self.tab_index = Tab::Search;
+            self.user_input.set(query.clone());
+            self.search(query);
+        }
+    }
+
+    fn save_search_history(&mut self) {
+        if let Err(err) = self.search_history.save() {
+            self.log(
+                format!("Couldn't save the search history.\n{}\n", err),
+                LogLevel::Warning,
+            );
+        }
+    }
+
+    /// Installs the selected addon, or all marked addons after a
+    /// confirmation.
+    pub fn download(&mut self) {
+        if self.tab_index != Tab::Search || self.configured_path().is_none() {
+            return;
+        }
+        let marked = self.search_table.marked_items();
+        if !marked.is_empty() {
+            self.confirm_marked("install", &marked, App::install_marked);
+            return;
+        }
+        if let Some(item) = self.search_table.get_selected().cloned() {
+            self.install(&item);
+        }
+    }
+
+    pub fn install_marked(&mut self) {
+        for item in self.search_table.marked_items().iter() {
+            self.install(item);
+        }
+        self.search_table.marked.clear();
+    }
+
+    /// Asks whether `action` should be applied to the marked `items`.
+    fn confirm_marked(
+        &mut self,
+        action: &str,
+        items: &[TableItem],
+        callback: fn(&mut App),
+    ) {
+        let names: Vec<String> =
+            items.iter().map(|i| i.addon.name.clone()).collect();
+        self.add_dialog(
+            format!(
+                "Do you want to {} these {} addons?\n{}",
+                action,
+                items.len(),
+                names.join(", ")
+            ),
+            Some(callback),
+        );
+    }
+
+    /// Reads the file ids of the installed addons for the search tab.
+    fn load_installed_files(&mut self) {
+        self.installed_files =
+            match AddonManager::load_addon_db(&self.get_save_path()) {
+                Ok(addons) => addons
+                    .addons
+                    .into_iter()
+                    .map(|a| (a.addon_id, a.file_id))
+                    .collect(),
+                Err(_) => HashMap::new(),
+            };
+    }
+
+    /// Installs the addon of a search result. An addon that is already
+    /// installed is updated or reinstalled instead, and marked as explicitly
+    /// installed if it was only a dependency so far.
+    fn install(&mut self, item: &TableItem) {
+        let addon = item.addon.clone();
+        let download_url = item.download_url.clone();
+        let save_path = self.get_save_path();
+        let installed =
+            AddonManager::load_addon_db(&save_path)
+                .ok()
+                .and_then(|addons| {
+                    addons
+                        .addons
+                        .into_iter()
+                        .find(|a| a.addon_id == addon.addon_id)
+                });
+        if installed
+            .as_ref()
+            .is_some_and(|a| a.install_reason == InstallReason::Dependency)
+        {
+            match AddonManager::mark_explicit(&save_path, &addon.addon_id) {
+                Ok(_) => self.log(
+                    format!(
+                        "{} is now marked as explicitly installed.\n",
+                        addon.name
+                    ),
+                    LogLevel::Info,
+                ),
+                Err(err) => {
+                    self.log(
+                        format!(
+                            "Couldn't mark {} as explicitly installed.\n{}\n",
+                            addon.name, err
+                        ),
+                        LogLevel::Error,
+                    );
+                    return;
+                }
+            }
+        }
+        let (verb, done, kind) = match &installed {
+            None => ("install", "installed", OperationKind::Install),
+            Some(a) if is_newer_file(&addon.file_id, &a.file_id) => {
+                ("update", "updated", OperationKind::Update)
+            }
+            Some(_) => ("reinstall", "reinstalled", OperationKind::Reinstall),
+        };
+        self.warn_folder_conflicts(&save_path, &addon);
+        let result = installed
+            .as_ref()
+            .map_or(Ok(()), |a| AddonManager::delete(&save_path, a))
+            .and_then(|_| {
+                CurseForgeAPI::download(
+                    &download_url,
+                    &save_path,
+                    &self.settings.network,
+                )
+            })
+            .and_then(|_| AddonManager::add_to_db(&save_path, addon.clone()));
+        let from = installed.as_ref().map(|a| a.version.as_str());
+        self.record(kind, &addon, (from, Some(&addon.version)), &result);
+        if let Err(err) = result {
+            self.log(
+                format!("Couldn't {} {}.\n{}\n", verb, addon.name, err),
+                LogLevel::Error,
+            );
+        } else {
+            self.log(
+                format!("{} successfully {}.\n", addon.name, done),
+                LogLevel::Info,
+            );
+            self.install_dependencies(&save_path, &addon);
+        }
+        self.load_installed_files();
+    }
+
+    fn warn_folder_conflicts(&mut self, path: &str, addon: &Addon) {
+        match AddonManager::folder_conflicts(path, addon) {
+            Ok(conflicts) => {
+                for conflict in conflicts.iter() {
+                    self.log(
+                        format!(
+                            "{} overwrites the folder {} which is also used by {}.\n",
+                            addon.name,
+                            conflict.folder,
+                            conflict.owners.join(", ")
+                        ),
+                        LogLevel::Warning,
+                    );
+                }
+            }
+            Err(err) => self.log(
+                format!("Couldn't check folders of {}.\n{}\n", addon.name, err),
+                LogLevel::Error,
+            ),
+        }
+    }
+
+    /// Installs all required dependencies of `addon` (and their own
+    /// dependencies) that are not installed yet.
+    fn install_dependencies(&mut self, save_path: &str, addon: &Addon) {
+        let mut pending = addon.dependencies.clone();
+        while !pending.is_empty() {
+            let installed = match AddonManager::load_addon_db(save_path) {
+                Ok(a) => a.addons,
+                Err(err) => {
+                    self.log(
+                        format!("Couldn't parse addons.\n{}\n", err),
+                        LogLevel::Error,
+                    );
+                    return;
+                }
+            };
+            let mut missing: Vec<i32> = pending
+                .iter()
+                .filter(|id| !installed.iter().any(|a| &a.addon_id == *id))
+                .filter_map(|id| id.parse::<i32>().ok())
+                .collect();
+            missing.sort_unstable();
+            missing.dedup();
+            pending.clear();
+            if missing.is_empty() {
+                break;
+            }
+            let available = match CurseForgeAPI::check_for_updates(
+                missing.clone(),
+                &self.get_curse_flavor(),
+                &self.settings.network,
+            ) {
+                Ok(a) => a,
+                Err(err) => {
+                    self.log(
+                        format!(
+                            "Couldn't fetch dependencies of {}.\n{}\n",
+                            addon.name, err
+                        ),
+                        LogLevel::Error,
+                    );
+                    return;
+                }
+            };
+            for id in missing.iter() {
+                let mut dependency = match available.get(&id.to_string()) {
+                    Some(d) => d.clone(),
+                    None => {
+                        self.log(
+                            format!(
+                                "Dependency {} of {} is not available for this version.\n",
+                                id, addon.name
+                            ),
+                            LogLevel::Warning,
+                        );
+                        continue;
+                    }
+                };
+                dependency.install_reason = InstallReason::Dependency;
+                self.warn_folder_conflicts(save_path, &dependency);
+                let result = CurseForgeAPI::download(
+                    &dependency.download_url,
+                    save_path,
+                    &self.settings.network,
+                )
+                .and_then(|_| {
+                    AddonManager::add_to_db(save_path, dependency.clone())
+                });
+                self.record(
+                    OperationKind::Install,
+                    &dependency,
+                    (None, Some(&dependency.version)),
+                    &result,
+                );
+                if let Err(err) = result {
+                    self.log(
+                        format!(
+                            "Couldn't install dependency {}.\n{}\n",
+                            dependency.name, err
+                        ),
+                        LogLevel::Error,
+                    );
+                } else {
+                    self.log(
+                        format!(
+                            "Dependency {} successfully installed.\n",
+                            dependency.name
+                        ),
+                        LogLevel::Info,
+                    );
+                    pending.extend(dependency.dependencies.clone());
+                }
             }
-            self.log(msg, log_level);
         }
     }
 
     pub fn update_all(&mut self) {
         if self.tab_index == Tab::Installed {
-            let save_path = self.get_save_path();
+            let save_path = match self.configured_path() {
+                Some(path) => path,
+                None => return,
+            };
+            let installed = AddonManager::load_addon_db(&save_path)
+                .map(|a| a.addons)
+                .unwrap_or_default();
             for item in self.updates.clone().iter() {
                 let msg;
                 let log_level;
-                if let Err(err) = AddonManager::delete(&save_path, &item)
+                self.warn_folder_conflicts(&save_path, item);
+                let result = AddonManager::delete(&save_path, item)
                     .and_then(|_| {
-                        CurseForgeAPI::download(&item.download_url, &save_path)
+                        CurseForgeAPI::download(
+                            &item.download_url,
+                            &save_path,
+                            &self.settings.network,
+                        )
                     })
                     .and_then(|_| {
                         AddonManager::add_to_db(&save_path, item.clone())
-                    })
-                {
+                    });
+                let from = installed
+                    .iter()
+                    .find(|a| a.addon_id == item.addon_id)
+                    .map(|a| a.version.as_str());
+                self.record(
+                    OperationKind::Update,
+                    item,
+                    (from, Some(&item.version)),
+                    &result,
+                );
+                if let Err(err) = result {
                     msg = format!(
                         "Couldn't update {}.\n{}\n",
                         item.name.clone(),
                         err
                     );
                     log_level = LogLevel::Error;
+                    self.log(msg, log_level);
                 } else {
                     msg = format!(
                         "{} successfully updated.\n",
                         item.name.clone()
                     );
                     log_level = LogLevel::Info;
+                    self.log(msg, log_level);
+                    self.install_dependencies(&save_path, item);
                 }
-                self.log(msg, log_level);
             }
         }
     }
 
+    /// Updates the selected addon, or all marked addons after a
+    /// confirmation.
     pub fn update_addon(&mut self) {
-        if self.tab_index == Tab::Installed {
-            let save_path = self.get_save_path();
-            let item = self.installed_table.get_selected().unwrap();
-            let update = self
-                .updates
-                .iter()
-                .find(|&u| u.addon_id == item.addon.addon_id)
-                .unwrap();
-            let name = &item.cells[1];
-            let msg;
-            let log_level;
-            if let Err(err) = AddonManager::delete(&save_path, &item.addon)
-                .and_then(|_| {
-                    CurseForgeAPI::download(&item.download_url, &save_path)
-                })
-                .and_then(|_| {
-                    AddonManager::add_to_db(&save_path, update.clone())
-                })
-            {
-                msg = format!("Couldn't update {}.\n{}\n", name, err);
-                log_level = LogLevel::Error;
-            } else {
-                msg = format!("{} successfully updated.\n", name);
-                log_level = LogLevel::Info;
-                self.load_installed_addons();
+        if self.tab_index != Tab::Installed || self.configured_path().is_none()
+        {
+            return;
+        }
+        let marked = self.installed_table.marked_items();
+        if !marked.is_empty() {
+            self.confirm_marked("update", &marked, App::update_marked);
+            return;
+        }
+        if let Some(item) = self.installed_table.get_selected() {
+            let installed = item.addon.clone();
+            self.update(&installed);
+            self.load_installed_addons();
+        }
+    }
+
+    pub fn update_marked(&mut self) {
+        for item in self.installed_table.marked_items().iter() {
+            self.update(&item.addon);
+        }
+        self.installed_table.marked.clear();
+        self.load_installed_addons();
+    }
+
+    fn update(&mut self, installed: &Addon) {
+        let update = match self
+            .updates
+            .iter()
+            .find(|&u| u.addon_id == installed.addon_id)
+        {
+            Some(update) => update.clone(),
+            None => {
+                self.log(
+                    format!("{} is already up-to-date.\n", installed.name),
+                    LogLevel::Info,
+                );
+                return;
             }
+        };
+        let save_path = self.get_save_path();
+        let msg;
+        let log_level;
+        self.warn_folder_conflicts(&save_path, &update);
+        let result = AddonManager::delete(&save_path, installed)
+            .and_then(|_| {
+                CurseForgeAPI::download(
+                    &update.download_url,
+                    &save_path,
+                    &self.settings.network,
+                )
+            })
+            .and_then(|_| AddonManager::add_to_db(&save_path, update.clone()));
+        self.record(
+            OperationKind::Update,
+            &update,
+            (Some(&installed.version), Some(&update.version)),
+            &result,
+        );
+        if let Err(err) = result {
+            msg = format!("Couldn't update {}.\n{}\n", installed.name, err);
+            log_level = LogLevel::Error;
+            self.log(msg, log_level);
+        } else {
+            msg = format!("{} successfully updated.\n", installed.name);
+            log_level = LogLevel::Info;
             self.log(msg, log_level);
+            self.install_dependencies(&save_path, &update);
+        }
+    }
+
+    pub fn confirm_remove_addon(&mut self) {
+        if self.tab_index != Tab::Installed || self.configured_path().is_none()
+        {
+            return;
+        }
+        let marked = self.installed_table.marked_items();
+        if !marked.is_empty() {
+            self.confirm_marked("delete", &marked, App::remove_marked);
+        } else if self.installed_table.get_selected().is_some() {
+            self.add_dialog(
+                "Do you want to delete this addon?".to_string(),
+                Some(App::remove_addon),
+            );
         }
     }
 
     pub fn remove_addon(&mut self) {
-        if self.tab_index == Tab::Installed {
-            let path = match self.selected_version {
-                Version::Classic => &self.classic_path,
-                Version::Retail => &self.retail_path,
-                Version::Tbc => &self.tbc_path,
-            };
-            let item = self.installed_table.get_selected().unwrap();
+        if let Some(item) = self.installed_table.get_selected() {
+            let addon = item.addon.clone();
+            self.remove(&addon);
+            self.load_installed_addons();
+        }
+    }
+
+    pub fn remove_marked(&mut self) {
+        for item in self.installed_table.marked_items().iter() {
+            self.remove(&item.addon);
+        }
+        self.installed_table.marked.clear();
+        self.load_installed_addons();
+    }
+
+    fn remove(&mut self, addon: &Addon) {
+        let path = self.get_save_path();
+        let msg;
+        let log_level;
+        let result = AddonManager::delete(&path, addon);
+        self.record(
+            OperationKind::Remove,
+            addon,
+            (Some(&addon.version), None),
+            &result,
+        );
+        match result {
+            Ok(_) => {
+                msg = format!("{} successfully deleted.\n", addon.name);
+                log_level = LogLevel::Info;
+            }
+            Err(err) => {
+                msg = format!("Couldn't delete {}.\n{}\n", addon.name, err);
+                log_level = LogLevel::Error;
+            }
+        }
+        self.log(msg, log_level);
+    }
+
+    pub fn find_orphans(&mut self) -> Vec<Addon> {
+        match AddonManager::find_orphans(&self.get_save_path()) {
+            Ok(orphans) => orphans,
+            Err(err) => {
+                self.log(
+                    format!("Couldn't parse addons.\n{}\n", err),
+                    LogLevel::Error,
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn confirm_autoremove(&mut self) {
+        if self.configured_path().is_none() {
+            return;
+        }
+        let orphans = self.find_orphans();
+        if orphans.is_empty() {
+            self.log(
+                "No orphaned dependencies found.\n".to_string(),
+                LogLevel::Info,
+            );
+            return;
+        }
+        let names: Vec<String> =
+            orphans.iter().map(|a| a.name.clone()).collect();
+        self.add_dialog(
+            format!(
+                "Do you want to remove these unused dependencies?\n{}",
+                names.join(", ")
+            ),
+            Some(App::autoremove),
+        );
+    }
+
+    pub fn autoremove(&mut self) {
+        let path = self.get_save_path();
+        for addon in self.find_orphans().iter() {
             let msg;
             let log_level;
-            match AddonManager::delete(&path, &item.addon) {
+            let result = AddonManager::delete(&path, addon);
+            self.record(
+                OperationKind::Remove,
+                addon,
+                (Some(&addon.version), None),
+                &result,
+            );
+            match result {
                 Ok(_) => {
-                    msg = format!("{} successfully deleted.\n", &item.cells[1]);
+                    msg = format!("{} successfully deleted.\n", addon.name);
                     log_level = LogLevel::Info;
                 }
                 Err(err) => {
-                    msg = format!(
-                        "Couldn't delete {}.\n{}\n",
-                        &item.cells[1], err
-                    );
+                    msg = format!("Couldn't delete {}.\n{}\n", addon.name, err);
                     log_level = LogLevel::Error;
                 }
             }
-            self.load_installed_addons();
             self.log(msg, log_level);
         }
+        if self.tab_index == Tab::Installed {
+            self.load_installed_addons();
+        }
     }
 
     fn centered_rect(&self, percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -598,11 +2459,34 @@ impl App {
     pub fn next_table_item(&mut self) {
         match self.tab_index {
             Tab::Search => {
+                let len = self.search_table.items.len();
+                if self.search_more
+                    && self.search_table.state.selected().map(|i| i + 1)
+                        == Some(len)
+                {
+                    self.load_more_results();
+                }
                 self.search_table.next();
             }
             Tab::Installed => {
                 self.installed_table.next();
             }
+            Tab::History => {
+                let len = self.filtered_operations().len();
+                let i = match self.operations_state.selected() {
+                    Some(i) if i + 1 < len => i + 1,
+                    _ => 0,
+                };
+                self.operations_state.select(Some(i).filter(|_| len > 0));
+            }
+            Tab::Settings => {
+                let len = self.settings.entries().len();
+                let i = match self.settings_state.selected() {
+                    Some(i) if i + 1 < len => i + 1,
+                    _ => 0,
+                };
+                self.settings_state.select(Some(i));
+            }
         }
     }
 
@@ -614,27 +2498,460 @@ impl App {
             Tab::Installed => {
                 self.installed_table.previous();
             }
+            Tab::History => {
+                let len = self.filtered_operations().len();
+                let i = match self.operations_state.selected() {
+                    Some(i) if i > 0 => i - 1,
+                    _ => len.saturating_sub(1),
+                };
+                self.operations_state.select(Some(i).filter(|_| len > 0));
+            }
+            Tab::Settings => {
+                let len = self.settings.entries().len();
+                let i = match self.settings_state.selected() {
+                    Some(i) if i > 0 => i - 1,
+                    _ => len.saturating_sub(1),
+                };
+                self.settings_state.select(Some(i));
+            }
         }
     }
 
-    pub fn select_classic(&mut self) {
-        self.selected_version = Version::Classic;
-        self.log("Switched to Classic.\n".to_string(), LogLevel::Info);
-        self.refresh_view();
+    /// Clicks select tabs, flavors, table rows and dialog buttons; the wheel
+    /// scrolls the log and the tables.
+    pub fn handle_mouse(&mut self, event: MouseEvent) {
+        // Termion counts from 1, tui from 0.
+        let (button, x, y) = match event {
+            MouseEvent::Press(button, x, y) => {
+                (button, x.saturating_sub(1), y.saturating_sub(1))
+            }
+            _ => return,
+        };
+        match self.mode {
+            Mode::Normal => (),
+            Mode::Dialog => {
+                let clicked = self
+                    .areas
+                    .buttons
+                    .iter()
+                    .find(|(area, _)| contains(*area, x, y))
+                    .map(|(_, confirm)| *confirm);
+                match (button, clicked) {
+                    (MouseButton::Left, Some(true)) => self.confirm_dialog(),
+                    (MouseButton::Left, Some(false)) => self.stop_dialog(),
+                    _ => (),
+                }
+                return;
+            }
+            _ => return,
+        }
+        let in_log = contains(self.areas.log, x, y);
+        let in_rows = contains(self.areas.rows, x, y);
+        match button {
+            MouseButton::WheelDown if in_log => self.scroll_down_log(),
+            MouseButton::WheelUp if in_log => self.scroll_up_log(),
+            MouseButton::WheelDown if in_rows => self.scroll_table(true),
+            MouseButton::WheelUp if in_rows => self.scroll_table(false),
+            MouseButton::Left if in_rows => {
+                let row = (y - self.areas.rows.y) as usize;
+                match self.tab_index {
+                    Tab::Installed => self.installed_table.select_row(row),
+                    Tab::Search => self.search_table.select_row(row),
+                    Tab::History => {
+                        let index = self.operations_offset + row;
+                        if index < self.filtered_operations().len() {
+                            self.operations_state.select(Some(index));
+                        }
+                    }
+                    Tab::Settings => {
+                        let index = self.settings_offset + row;
+                        if index < self.settings.entries().len() {
+                            self.settings_state.select(Some(index));
+                        }
+                    }
+                }
+            }
+            MouseButton::Left if contains(self.areas.tabs, x, y) => {
+                if let Some(tab) = tab_at(&TAB_TITLES, self.areas.tabs, x)
+                    .and_then(Tab::from)
+                    .filter(|tab| *tab != self.tab_index)
+                {
+                    self.tab_index = tab;
+                    self.load_tab();
+                }
+            }
+            MouseButton::Left if contains(self.areas.flavors, x, y) => {
+                let names: Vec<&str> = self
+                    .settings
+                    .flavors
+                    .iter()
+                    .map(|f| f.name.as_str())
+                    .collect();
+                if let Some(index) = tab_at(&names, self.areas.flavors, x)
+                    .filter(|index| *index != self.selected_flavor)
+                {
+                    self.select_flavor(index);
+                }
+            }
+            _ => (),
+        }
     }
 
-    pub fn select_retail(&mut self) {
-        self.selected_version = Version::Retail;
-        self.log("Switched to Retail.\n".to_string(), LogLevel::Info);
-        self.refresh_view();
+    /// Moves the selection of the current table without wrapping around.
+    fn scroll_table(&mut self, down: bool) {
+        let (selected, len) = match self.tab_index {
+            Tab::Installed => (
+                self.installed_table.state.selected(),
+                self.installed_table.items.len(),
+            ),
+            Tab::Search => (
+                self.search_table.state.selected(),
+                self.search_table.items.len(),
+            ),
+            Tab::History => (
+                self.operations_state.selected(),
+                self.filtered_operations().len(),
+            ),
+            Tab::Settings => (
+                self.settings_state.selected(),
+                self.settings.entries().len(),
+            ),
+        };
+        let more = self.tab_index == Tab::Search && self.search_more;
+        if down && (more || selected.is_none_or(|i| i + 1 < len)) {
+            self.next_table_item();
+        } else if !down && selected.is_some_and(|i| i > 0) {
+            self.prev_table_item();
+        }
+    }
+
+    pub fn toggle_mark(&mut self) {
+        match self.tab_index {
+            Tab::Search => self.search_table.toggle_mark(),
+            Tab::Installed => self.installed_table.toggle_mark(),
+            Tab::History | Tab::Settings => (),
+        }
+    }
+
+    pub fn first_table_item(&mut self) {
+        match self.tab_index {
+            Tab::Search => self.search_table.first(),
+            Tab::Installed => self.installed_table.first(),
+            Tab::History => self.select_first_operation(),
+            Tab::Settings => self.settings_state.select(Some(0)),
+        }
+    }
+
+    pub fn last_table_item(&mut self) {
+        match self.tab_index {
+            Tab::Search => self.search_table.last(),
+            Tab::Installed => self.installed_table.last(),
+            Tab::History => {
+                let len = self.filtered_operations().len();
+                self.operations_state.select(len.checked_sub(1));
+            }
+            Tab::Settings => {
+                let len = self.settings.entries().len();
+                self.settings_state.select(Some(len.saturating_sub(1)));
+            }
+        }
+    }
+
+    pub fn select_flavor(&mut self, index: usize) {
+        if let Some(flavor) = self.settings.flavors.get(index) {
+            self.installed_table.marked.clear();
+            self.search_table.marked.clear();
+            let msg = format!("Switched to {}.\n", flavor.name);
+            self.selected_flavor = index;
+            self.log(msg, LogLevel::Info);
+            self.refresh_view();
+        }
+    }
+
+    /// Looks for World of Warcraft installations that are not configured yet
+    /// and asks whether they should be added to the config.
+    pub fn detect_installations(&mut self) {
+        let flavors = &self.settings.flavors;
+        self.detected_installations =
+            InstallationDetector::find_installations()
+                .into_iter()
+                .filter(|i| {
+                    !flavors.iter().any(|f| {
+                        f.path == i.path
+                            || (f.name == i.name && !f.path.is_empty())
+                    })
+                })
+                .collect();
+        if self.detected_installations.is_empty() {
+            return;
+        }
+        let found: Vec<String> = self
+            .detected_installations
+            .iter()
+            .map(|i| format!("{}: {}", i.name, i.path))
+            .collect();
+        self.add_dialog(
+            format!(
+                "Found World of Warcraft installations:\n{}\nDo you want to add them to your config?",
+                found.join("\n")
+            ),
+            Some(App::apply_detected_installations),
+        );
     }
 
-    pub fn select_tbc(&mut self) {
-        self.selected_version = Version::Tbc;
-        self.log("Switched to Tbc.\n".to_string(), LogLevel::Info);
+    pub fn apply_detected_installations(&mut self) {
+        let installations = std::mem::take(&mut self.detected_installations);
+        for installation in installations.into_iter() {
+            let path = installation.path.clone();
+            self.add_installation(installation);
+            if let Err(err) = fs::create_dir_all(&path)
+                .map_err(|err| err.into())
+                .and_then(|_| AddonManager::init_addon_db(&path))
+            {
+                self.log(
+                    format!(
+                        "Couldn't load addon directory {}.\n{}\n",
+                        path, err
+                    ),
+                    LogLevel::Error,
+                );
+            }
+        }
+        match self.settings.save() {
+            Ok(_) => self.log(
+                format!(
+                    "Saved detected installations to {}.\n",
+                    self.settings.config_path
+                ),
+                LogLevel::Info,
+            ),
+            Err(err) => self.log(
+                format!("Couldn't save config.\n{}\n", err),
+                LogLevel::Error,
+            ),
+        }
         self.refresh_view();
     }
 
+    /// Sets the path of the flavor with the same name as `installation` if it
+    /// has none yet, or adds a new flavor for it.
+    fn add_installation(&mut self, installation: Installation) {
+        match self
+            .settings
+            .flavors
+            .iter_mut()
+            .find(|f| f.name == installation.name)
+        {
+            Some(flavor) => {
+                if flavor.path.is_empty() {
+                    flavor.path = installation.path;
+                }
+            }
+            None => self.settings.flavors.push(Flavor {
+                name: installation.name,
+                path: installation.path,
+                curse_flavor: installation.curse_flavor,
+                key: Key::Null,
+            }),
+        }
+    }
+
+    /// Opens the first-run wizard with the paths of all detected
+    /// installations already filled in. The wizard edits the flavors in
+    /// place, so they are copied first to be restored on cancel.
+    pub fn start_setup(&mut self) {
+        let flavors = self.settings.flavors.clone();
+        for installation in InstallationDetector::find_installations() {
+            self.add_installation(installation);
+        }
+        self.setup = Some((SetupWizard::new(&self.settings.flavors), flavors));
+        self.mode = Mode::Setup;
+    }
+
+    pub fn handle_setup_key(&mut self, key: Key) {
+        let setup = match self.setup.as_mut() {
+            Some((setup, _)) => setup,
+            None => return,
+        };
+        match setup.handle_key(key, &mut self.settings.flavors) {
+            SetupResult::Continue => (),
+            SetupResult::Finished => {
+                self.setup = None;
+                self.mode = Mode::Normal;
+                self.select_configured_flavor();
+                match self.settings.save() {
+                    Ok(_) => self.log(
+                        format!(
+                            "Setup finished. Config saved to {}.\n",
+                            self.settings.config_path
+                        ),
+                        LogLevel::Info,
+                    ),
+                    Err(err) => self.log(
+                        format!("Couldn't save config.\n{}\n", err),
+                        LogLevel::Error,
+                    ),
+                }
+                self.refresh_view();
+            }
+            SetupResult::Cancelled => {
+                if let Some((_, flavors)) = self.setup.take() {
+                    self.settings.flavors = flavors;
+                }
+                self.mode = Mode::Normal;
+                self.log(
+                    "Setup cancelled. Your config was not changed.\n"
+                        .to_string(),
+                    LogLevel::Warning,
+                );
+            }
+        }
+    }
+
+    /// Handles a key in normal mode. Keys are collected until they match a
+    /// binding; a key that doesn't continue any sequence starts a new one.
+    pub fn handle_key(&mut self, key: Key) {
+        self.pending_keys.push(key);
+        match self.settings.resolve(&self.pending_keys) {
+            KeyMatch::Exact(action) => {
+                self.pending_keys.clear();
+                self.pending_since = None;
+                action.run(self);
+            }
+            KeyMatch::Ambiguous(_) | KeyMatch::Prefix => {
+                self.pending_since = Some(Instant::now());
+            }
+            KeyMatch::None => {
+                let retry = self.pending_keys.len() > 1;
+                self.pending_keys.clear();
+                self.pending_since = None;
+                if retry {
+                    self.handle_key(key);
+                }
+            }
+        }
+    }
+
+    /// Runs the action of an ambiguous sequence once no further key was
+    /// pressed in time and reloads the config file if it was changed.
+    pub fn on_tick(&mut self) {
+        if !matches!(self.mode, Mode::Setup) && self.settings.config_changed() {
+            self.reload_config();
+        }
+        let expired = self
+            .pending_since
+            .is_some_and(|since| since.elapsed() >= SEQUENCE_TIMEOUT);
+        if !expired {
+            return;
+        }
+        let action = match self.settings.resolve(&self.pending_keys) {
+            KeyMatch::Ambiguous(action) => Some(action),
+            _ => None,
+        };
+        self.pending_keys.clear();
+        self.pending_since = None;
+        if let Some(action) = action {
+            action.run(self);
+        }
+    }
+
+    /// Applies changes made to the config file while the addon manager is
+    /// running and logs them.
+    fn reload_config(&mut self) {
+        let (settings, diagnostics) = match self.settings.reload() {
+            Ok(result) => result,
+            Err(err) => {
+                self.log(
+                    format!(
+                        "Couldn't reload {}.\n{}\n",
+                        self.settings.config_path, err
+                    ),
+                    LogLevel::Error,
+                );
+                return;
+            }
+        };
+        let changes = settings.changes_from(&self.settings);
+        let old = std::mem::replace(&mut self.settings, settings);
+        logger::configure(&self.settings.log);
+        for diagnostic in diagnostics.iter() {
+            self.log(format!("{}\n", diagnostic), LogLevel::Warning);
+        }
+        if changes.is_empty() {
+            return;
+        }
+        self.log(
+            format!("Reloaded {}.\n", self.settings.config_path),
+            LogLevel::Info,
+        );
+        for change in changes.iter() {
+            self.log(format!("{}\n", change), LogLevel::Info);
+        }
+
+        for flavor in self.settings.flavors.clone().iter() {
+            let unchanged = old
+                .flavors
+                .iter()
+                .any(|f| f.name == flavor.name && f.path == flavor.path);
+            if unchanged || flavor.path.is_empty() {
+                continue;
+            }
+            if let Err(err) = AddonManager::init_addon_db(&flavor.path) {
+                self.log(
+                    format!(
+                        "Couldn't load {} addon directory.\n{}\n",
+                        flavor.name, err
+                    ),
+                    LogLevel::Error,
+                );
+            }
+        }
+        let selected = old.flavors.get(self.selected_flavor).and_then(|f| {
+            self.settings.flavors.iter().position(|n| n.name == f.name)
+        });
+        match selected {
+            Some(index) => self.selected_flavor = index,
+            None => self.select_configured_flavor(),
+        }
+        self.pending_keys.clear();
+        self.pending_since = None;
+        if self.tab_index == Tab::Installed {
+            self.load_installed_addons();
+        }
+    }
+
+    pub fn open_palette(&mut self) {
+        self.palette = Some(CommandPalette::new(&self.settings));
+        self.mode = Mode::Palette;
+    }
+
+    pub fn handle_palette_key(&mut self, key: Key) {
+        let palette = match self.palette.as_mut() {
+            Some(palette) => palette,
+            None => return,
+        };
+        match palette.handle_key(key) {
+            PaletteResult::Continue => {}
+            PaletteResult::Run(action) => {
+                self.palette = None;
+                self.mode = Mode::Normal;
+                action.run(self);
+            }
+            PaletteResult::Cancelled => {
+                self.palette = None;
+                self.mode = Mode::Normal;
+            }
+        }
+    }
+
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    pub fn toggle_log(&mut self) {
+        self.log_collapsed = !self.log_collapsed;
+    }
+
     pub fn scroll_up_log(&mut self) {
         if self.log_scroll > 0 {
             self.log_scroll -= 1;
@@ -650,48 +2967,155 @@ impl App {
     pub fn select_next_tab(&mut self) {
         let index = (self.tab_index as usize + 1).rem_euclid(Tab::len());
         self.tab_index = Tab::from(index).unwrap();
-        match self.tab_index {
-            Tab::Installed => self.load_installed_addons(),
-            _ => (),
-        };
+        self.load_tab();
     }
 
     pub fn select_prev_tab(&mut self) {
         let index =
             (self.tab_index as isize - 1).rem_euclid(Tab::len() as isize);
         self.tab_index = Tab::from(index as usize).unwrap();
-        match self.tab_index {
-            Tab::Installed => self.load_installed_addons(),
-            _ => (),
-        };
-    }
-
-    pub fn select_installed(&mut self) {
-        self.load_installed_addons();
-        self.tab_index = Tab::Installed;
+        self.load_tab();
     }
 
     pub fn select_search(&mut self) {
         self.mode = Mode::Editing;
+        self.history_index = None;
         self.tab_index = Tab::Search;
+        self.load_tab();
+    }
+
+    /// Fills the selected tab when it is shown.
+    fn load_tab(&mut self) {
+        match self.tab_index {
+            Tab::Installed => self.load_installed_addons(),
+            Tab::Search if !self.search_loaded => self.browse(),
+            Tab::History => self.load_operations(),
+            _ => (),
+        }
     }
 
     pub fn log(&mut self, msg: String, log_level: LogLevel) {
+        let level = match log_level {
+            LogLevel::Info => Level::Info,
+            LogLevel::Warning => Level::Warn,
+            LogLevel::Error => Level::Error,
+        };
+        log::log!(level, "{}", msg.trim_end());
         self.log_messages.push((msg, log_level));
     }
 
     pub fn refresh_view(&mut self) {
         match self.tab_index {
             Tab::Installed => self.load_installed_addons(),
-            Tab::Search => self.search(self.user_input.clone()),
+            Tab::Search => self.search(self.search_query.clone()),
+            Tab::History => self.load_operations(),
+            Tab::Settings => (),
         };
     }
 
     pub fn get_save_path(&self) -> String {
-        match self.selected_version {
-            Version::Classic => self.classic_path.clone(),
-            Version::Retail => self.retail_path.clone(),
-            Version::Tbc => self.tbc_path.clone(),
+        self.settings
+            .flavors
+            .get(self.selected_flavor)
+            .map(|f| f.path.clone())
+            .unwrap_or_default()
+    }
+
+    /// Selects the first flavor that has a path, so flavors skipped in the
+    /// setup aren't shown on startup.
+    fn select_configured_flavor(&mut self) {
+        self.selected_flavor = self
+            .settings
+            .flavors
+            .iter()
+            .position(|f| !f.path.is_empty())
+            .unwrap_or(0);
+    }
+
+    /// Returns the AddOns path of the selected flavor. Logs an error if the
+    /// flavor has no path, so nothing is installed or removed relative to
+    /// the working directory.
+    fn configured_path(&mut self) -> Option<String> {
+        let path = self.get_save_path();
+        if path.is_empty() {
+            self.log(
+                format!("No path configured for {}.\n", self.flavor_name()),
+                LogLevel::Error,
+            );
+            return None;
+        }
+        Some(path)
+    }
+
+    fn flavor_name(&self) -> String {
+        self.settings
+            .flavors
+            .get(self.selected_flavor)
+            .map(|f| f.name.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn get_curse_flavor(&self) -> String {
+        self.settings
+            .flavors
+            .get(self.selected_flavor)
+            .map(|f| f.curse_flavor.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.x && x < area.right() && y >= area.y && y < area.bottom()
+}
+
+/// Returns the rows of a bordered table with a header.
+fn table_rows(area: Rect) -> Rect {
+    Rect::new(
+        area.x + 1,
+        area.y + 3,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(4),
+    )
+}
+
+/// Returns the first visible row of a table after drawing it. tui doesn't
+/// expose the offset of a table, so this follows the rules tui uses to keep
+/// the selected row in view.
+fn scroll_offset(offset: usize, selected: Option<usize>, rows: u16) -> usize {
+    let rows = rows as usize;
+    match selected {
+        _ if rows == 0 => offset,
+        Some(i) if i + 1 >= rows + offset => i + 1 - rows,
+        Some(i) if i < offset => i,
+        Some(_) => offset,
+        None => 0,
+    }
+}
+
+/// Returns the index of the title at column `x` of a bordered `Tabs`
+/// widget. Every title is padded by a space on both sides and followed by a
+/// divider.
+fn tab_at(titles: &[&str], area: Rect, x: u16) -> Option<usize> {
+    let mut start = area.x + 1;
+    for (index, title) in titles.iter().enumerate() {
+        let end = start + title.width() as u16 + 2;
+        if x >= start && x < end {
+            return Some(index);
         }
+        start = end + 1;
     }
+    None
+}
+
+/// Splits a game version like "10.2.7" into numbers, so "9.0.5" sorts
+/// before "10.2.7". Parts that aren't numbers count as 0.
+fn version_parts(version: &str) -> Vec<u64> {
+    version.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+/// Whether the file id `available` is newer than `installed`. The ids are
+/// numbers, so "1000" is newer than "999".
+fn is_newer_file(available: &str, installed: &str) -> bool {
+    available.parse::<i64>().unwrap_or(0)
+        > installed.parse::<i64>().unwrap_or(0)
 }
@@ -1,100 +1,227 @@
 extern crate tempfile;
-use crate::addon_manager::Addon;
+use crate::addon_manager::{Addon, InstallReason};
 use crate::app::TableItem;
-use crate::app::Version;
+use crate::settings::Network;
 use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::path::PathBuf;
+use std::time::Duration;
 use tempfile::Builder;
 
-const BASE_URL: &str = "https://addons-ecs.forgesvc.net/api/v2";
+pub const BASE_URL: &str = "https://addons-ecs.forgesvc.net/api/v2";
 const GAMEID: &str = "1";
 const STABLE_RELEASE: usize = 1;
-const BETA_RELEASE: usize = 2;
-const ALPHA_RELEASE: usize = 3;
+const REQUIRED_DEPENDENCY: usize = 3;
+/// The category section of World of Warcraft addons.
+const ADDON_SECTION: usize = 1;
+/// Number of search results loaded at once.
+pub const PAGE_SIZE: usize = 50;
 
 pub struct CurseForgeAPI {}
 
+/// Project information shown in the details popup.
+pub struct AddonDetails {
+    pub name: String,
+    pub summary: String,
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+    pub website: String,
+    pub download_count: String,
+    /// Display name and date of the latest stable file for the flavor.
+    pub latest_file: Option<(String, String)>,
+    pub changelog: String,
+}
+
+/// The order of search results.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SearchSort {
+    Popularity,
+    LastUpdated,
+    Name,
+    TotalDownloads,
+}
+
+impl SearchSort {
+    pub fn next(self) -> SearchSort {
+        match self {
+            SearchSort::Popularity => SearchSort::LastUpdated,
+            SearchSort::LastUpdated => SearchSort::Name,
+            SearchSort::Name => SearchSort::TotalDownloads,
+            SearchSort::TotalDownloads => SearchSort::Popularity,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchSort::Popularity => "popularity",
+            SearchSort::LastUpdated => "last updated",
+            SearchSort::Name => "name",
+            SearchSort::TotalDownloads => "total downloads",
+        }
+    }
+
+    /// The `sort` parameter of the search API.
+    fn id(self) -> usize {
+        match self {
+            SearchSort::Popularity => 1,
+            SearchSort::LastUpdated => 2,
+            SearchSort::Name => 3,
+            SearchSort::TotalDownloads => 5,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+}
+
+/// Everything that defines a page of search results.
+pub struct SearchQuery<'a> {
+    pub filter: &'a str,
+    pub sort: SearchSort,
+    pub category: Option<&'a Category>,
+    /// The index of the first result.
+    pub index: usize,
+}
+
+pub struct SearchResults {
+    pub items: Vec<TableItem>,
+    /// Whether the API may have more results after this page.
+    pub more: bool,
+}
+
+/// The changelog of a single file.
+pub struct Changelog {
+    pub name: String,
+    pub date: String,
+    pub text: String,
+}
+
 impl CurseForgeAPI {
     #[tokio::main]
     pub async fn search(
-        addon: &str,
-        game_version: Version,
-    ) -> Result<Vec<TableItem>, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/addon/search?gameId={}&searchFilter={}",
-            BASE_URL, GAMEID, addon
-        );
-        let resp = reqwest::get(&url).await?.text().await?;
+        query: &SearchQuery<'_>,
+        flavor: &str,
+        network: &Network,
+    ) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let url = format!("{}/addon/search", network.base_url);
+        // The filter is typed by the user, so it has to be escaped.
+        let mut params = vec![
+            ("gameId", GAMEID.to_string()),
+            ("gameVersionFlavor", flavor.to_string()),
+            ("searchFilter", query.filter.to_string()),
+            ("sort", query.sort.id().to_string()),
+            ("pageSize", PAGE_SIZE.to_string()),
+            ("index", query.index.to_string()),
+        ];
+        if let Some(category) = query.category {
+            params.push(("categoryId", category.id.clone()));
+        }
+        let client = CurseForgeAPI::client(network)?;
+        let resp = CurseForgeAPI::get_with_query(&client, &url, &params)
+            .await?
+            .text()
+            .await?;
         let data: serde_json::Value = serde_json::from_str(&resp)?;
+        let results = data.as_array().ok_or("Unexpected search response.")?;
         let mut items = Vec::new();
-        for addon in data.as_array().unwrap().iter() {
-            match CurseForgeAPI::parse_json(addon, game_version) {
-                Some(a) => {
-                    items.push(TableItem {
-                        cells: vec![
-                            a.name.clone(),
-                            a.game_version.clone(),
-                            a.file_date.clone(),
-                            a.download_count.clone(),
-                        ],
-                        download_url: a.download_url.clone(),
-                        addon: a.clone(),
-                    });
-                }
-                None => {}
+        // Addons without a file for the flavor are skipped, so a page can
+        // have fewer items than requested even if more results follow.
+        for addon in results.iter() {
+            if let Some(a) = CurseForgeAPI::parse_json(addon, flavor) {
+                items.push(TableItem {
+                    cells: vec![
+                        a.name.clone(),
+                        a.game_version.clone(),
+                        a.file_date.clone(),
+                        a.download_count.clone(),
+                    ],
+                    download_url: a.download_url.clone(),
+                    addon: a.clone(),
+                });
             }
         }
-        Ok(items)
+        Ok(SearchResults {
+            items,
+            more: results.len() == PAGE_SIZE,
+        })
     }
 
-    fn parse_json(
-        json: &serde_json::Value,
-        game_version: Version,
-    ) -> Option<Addon> {
-        let latest_file = CurseForgeAPI::latest_file(json, game_version);
-        match latest_file {
-            Some(file) => {
-                let filedate = CurseForgeAPI::parse_date(&file["fileDate"]);
-                let download_count =
-                    CurseForgeAPI::parse_download_count(&json["downloadCount"]);
-                let modules: Vec<String> = file["modules"]
-                    .as_array()
-                    .unwrap()
-                    .iter()
-                    .map(|m| {
-                        m["foldername"].as_str().unwrap_or_default().to_string()
-                    })
-                    .collect();
-                let addon = Addon {
-                    addon_id: json["id"].to_string(),
-                    file_id: file["id"].to_string(),
-                    name: json["name"].as_str().unwrap_or_default().to_string(),
-                    file_date: filedate.to_string(),
-                    modules: modules,
-                    version: file["displayName"]
-                        .as_str()
-                        .unwrap_or_default()
-                        .to_string(),
-                    game_version: file["gameVersion"][0]
-                        .as_str()
-                        .unwrap_or_default()
-                        .to_string(),
-                    download_url: file["downloadUrl"]
-                        .as_str()
-                        .unwrap_or_default()
-                        .to_string(),
-                    download_count: download_count,
-                };
-                return Some(addon);
-            }
-            None => {
-                return None;
+    /// Fetches the addon categories, sorted by name.
+    #[tokio::main]
+    pub async fn categories(
+        network: &Network,
+    ) -> Result<Vec<Category>, Box<dyn std::error::Error>> {
+        let url =
+            format!("{}/category/section/{}", network.base_url, ADDON_SECTION);
+        let client = CurseForgeAPI::client(network)?;
+        let resp = CurseForgeAPI::get(&client, &url).await?.text().await?;
+        let data: serde_json::Value = serde_json::from_str(&resp)?;
+        let mut categories: Vec<Category> = data
+            .as_array()
+            .ok_or("Unexpected category response.")?
+            .iter()
+            .map(|c| Category {
+                id: c["id"].to_string(),
+                name: c["name"].as_str().unwrap_or_default().to_string(),
+            })
+            .collect();
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(categories)
+    }
+
+    fn parse_json(json: &serde_json::Value, flavor: &str) -> Option<Addon> {
+        let latest_file = CurseForgeAPI::latest_file(json, flavor);
+        latest_file.map(|file| {
+            let filedate = CurseForgeAPI::parse_date(&file["fileDate"]);
+            let download_count =
+                CurseForgeAPI::parse_download_count(&json["downloadCount"]);
+            let modules: Vec<String> = file["modules"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|m| {
+                    m["foldername"].as_str().unwrap_or_default().to_string()
+                })
+                .collect();
+            let dependencies: Vec<String> = file["dependencies"]
+                .as_array()
+                .map(|deps| {
+                    deps.iter()
+                        .filter(|d| d["type"] == REQUIRED_DEPENDENCY)
+                        .map(|d| d["addonId"].to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            Addon {
+                addon_id: json["id"].to_string(),
+                file_id: file["id"].to_string(),
+                name: json["name"].as_str().unwrap_or_default().to_string(),
+                file_date: filedate.to_string(),
+                modules,
+                version: file["displayName"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                game_version: file["gameVersion"][0]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                download_url: file["downloadUrl"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                download_count,
+                dependencies,
+                install_reason: InstallReason::Explicit,
+                install_date: String::new(),
             }
-        };
+        })
     }
 
     pub fn parse_date(filedate: &serde_json::Value) -> String {
@@ -120,38 +247,34 @@ impl CurseForgeAPI {
         s
     }
 
-    pub fn latest_file(
-        json: &serde_json::Value,
-        game_version: Version,
-    ) -> Option<&serde_json::Value> {
-        let game_version_flavor = match game_version {
-            Version::Classic => "wow_classic",
-            Version::Retail => "wow_retail",
-            Version::Tbc => "wow_burning_crusade",
-        };
-        let files = json["latestFiles"]
+    pub fn latest_file<'a>(
+        json: &'a serde_json::Value,
+        flavor: &str,
+    ) -> Option<&'a serde_json::Value> {
+        json["latestFiles"]
             .as_array()
             .unwrap()
             .iter()
             .filter(|&file| {
                 file["releaseType"] == STABLE_RELEASE
-                    && file["gameVersionFlavor"] == game_version_flavor
+                    && file["gameVersionFlavor"] == flavor
             })
-            .max_by(|a, b| b["id"].to_string().cmp(&a["id"].to_string()));
-        files
+            .max_by_key(|file| file["id"].as_i64())
     }
 
     #[tokio::main]
     pub async fn download(
         url: &str,
         save_path: &str,
+        network: &Network,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = Builder::new().tempdir()?;
-        let response = reqwest::get(url).await?;
+        let client = CurseForgeAPI::client(network)?;
+        let response = CurseForgeAPI::get(&client, url).await?;
         let fname = response
             .url()
             .path_segments()
-            .and_then(|segments| segments.last())
+            .and_then(|mut segments| segments.next_back())
             .and_then(|name| if name.is_empty() { None } else { Some(name) })
             .unwrap_or("tmp.bin");
         let fname = tmp_dir.path().join(fname);
@@ -159,49 +282,243 @@ impl CurseForgeAPI {
         let mut dest = File::create(fname.clone())?;
         let content = response.bytes().await?;
         dest.write_all(&content)?;
-        let file = fs::File::open(fname).unwrap();
-        let mut archive = zip::ZipArchive::new(file).unwrap();
+        let file = fs::File::open(fname)?;
+        let mut archive = zip::ZipArchive::new(file)?;
 
         for i in 0..archive.len() {
-            let mut outpath = PathBuf::from(save_path.clone());
-            let mut file = archive.by_index(i).unwrap();
-            outpath.push(file.sanitized_name());
+            let mut outpath = PathBuf::from(save_path);
+            let mut file = archive.by_index(i)?;
+            match file.enclosed_name() {
+                Some(name) => outpath.push(name),
+                None => continue,
+            }
 
-            if (&*file.name()).ends_with('/') {
-                fs::create_dir_all(&outpath).unwrap();
+            if file.name().ends_with('/') {
+                fs::create_dir_all(&outpath)?;
             } else {
                 if let Some(p) = outpath.parent() {
                     if !p.exists() {
-                        fs::create_dir_all(&p).unwrap();
+                        fs::create_dir_all(p)?;
                     }
                 }
-                let mut outfile = fs::File::create(&outpath).unwrap();
-                io::copy(&mut file, &mut outfile).unwrap();
+                let mut outfile = fs::File::create(&outpath)?;
+                io::copy(&mut file, &mut outfile)?;
             }
         }
 
         Ok(())
     }
 
+    #[tokio::main]
+    pub async fn details(
+        addon_id: &str,
+        flavor: &str,
+        network: &Network,
+    ) -> Result<AddonDetails, Box<dyn std::error::Error>> {
+        let client = CurseForgeAPI::client(network)?;
+        let url = format!("{}/addon/{}", network.base_url, addon_id);
+        let resp = CurseForgeAPI::get(&client, &url).await?.text().await?;
+        let json: serde_json::Value = serde_json::from_str(&resp)?;
+        let names = |key: &str| -> Vec<String> {
+            json[key]
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v["name"].as_str())
+                        .map(|name| name.to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let latest_file = CurseForgeAPI::latest_file(&json, flavor);
+        let changelog = match latest_file {
+            Some(file) => {
+                let url = format!(
+                    "{}/addon/{}/file/{}/changelog",
+                    network.base_url, addon_id, file["id"]
+                );
+                let html =
+                    CurseForgeAPI::get(&client, &url).await?.text().await?;
+                CurseForgeAPI::html_to_text(&html)
+            }
+            None => String::new(),
+        };
+        Ok(AddonDetails {
+            name: json["name"].as_str().unwrap_or_default().to_string(),
+            summary: json["summary"].as_str().unwrap_or_default().to_string(),
+            authors: names("authors"),
+            categories: names("categories"),
+            website: json["websiteUrl"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            download_count: CurseForgeAPI::parse_download_count(
+                &json["downloadCount"],
+            ),
+            latest_file: latest_file.map(|file| {
+                (
+                    file["displayName"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CurseForgeAPI::parse_date(&file["fileDate"]),
+                )
+            }),
+            changelog,
+        })
+    }
+
+    /// Returns the stable files of the flavor newer than `installed_id` up to
+    /// and including `latest_id`, newest first.
+    #[tokio::main]
+    pub async fn changelogs(
+        addon_id: &str,
+        installed_id: &str,
+        latest_id: &str,
+        flavor: &str,
+        network: &Network,
+    ) -> Result<Vec<Changelog>, Box<dyn std::error::Error>> {
+        let installed_id = installed_id.parse::<i64>().unwrap_or(0);
+        let latest_id = latest_id.parse::<i64>()?;
+        let client = CurseForgeAPI::client(network)?;
+        let url = format!("{}/addon/{}/files", network.base_url, addon_id);
+        let resp = CurseForgeAPI::get(&client, &url).await?.text().await?;
+        let json: serde_json::Value = serde_json::from_str(&resp)?;
+        let mut files: Vec<&serde_json::Value> = json
+            .as_array()
+            .map(|files| {
+                files
+                    .iter()
+                    .filter(|file| {
+                        let id = file["id"].as_i64().unwrap_or(0);
+                        file["releaseType"] == STABLE_RELEASE
+                            && file["gameVersionFlavor"] == flavor
+                            && id > installed_id
+                            && id <= latest_id
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort_by_key(|file| std::cmp::Reverse(file["id"].as_i64()));
+        let mut changelogs = Vec::new();
+        for file in files.into_iter() {
+            let url = format!(
+                "{}/addon/{}/file/{}/changelog",
+                network.base_url, addon_id, file["id"]
+            );
+            let html = CurseForgeAPI::get(&client, &url).await?.text().await?;
+            changelogs.push(Changelog {
+                name: file["displayName"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                date: CurseForgeAPI::parse_date(&file["fileDate"]),
+                text: CurseForgeAPI::html_to_text(&html),
+            });
+        }
+        Ok(changelogs)
+    }
+
+    /// Converts a changelog to plain text. Block elements become line
+    /// breaks, list items get a dash and all other tags are dropped.
+    fn html_to_text(html: &str) -> String {
+        let mut text = String::new();
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            text.push_str(&rest[..start]);
+            let end = match rest[start..].find('>') {
+                Some(end) => start + end,
+                None => {
+                    rest = &rest[start..];
+                    break;
+                }
+            };
+            let tag = rest[start + 1..end].trim().to_lowercase();
+            let name = tag
+                .trim_start_matches('/')
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            match name {
+                "br" | "p" | "div" | "ul" | "ol" | "tr" | "h1" | "h2"
+                | "h3" | "h4" | "h5" | "h6" => text.push('\n'),
+                "li" if !tag.starts_with('/') => text.push_str("\n- "),
+                _ => {}
+            }
+            rest = &rest[end + 1..];
+        }
+        text.push_str(rest);
+        let text = text
+            .replace("&nbsp;", " ")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&amp;", "&");
+        let mut lines: Vec<&str> = Vec::new();
+        for line in text.lines().map(|line| line.trim_end()) {
+            let blank = line.trim().is_empty();
+            if blank && lines.last().is_none_or(|l| l.trim().is_empty()) {
+                continue;
+            }
+            lines.push(line);
+        }
+        lines.join("\n").trim().to_string()
+    }
+
     #[tokio::main]
     pub async fn check_for_updates(
         addons: Vec<i32>,
-        game_version: Version,
+        flavor: &str,
+        network: &Network,
     ) -> Result<HashMap<String, Addon>, Box<dyn std::error::Error>> {
-        let url = format!("{}/addon", BASE_URL);
-        let client = reqwest::Client::new();
-        let resp = client.post(&url).json(&addons).send().await?.text().await?;
+        let url = format!("{}/addon", network.base_url);
+        let client = CurseForgeAPI::client(network)?;
+        log::debug!("POST {} {:?}", url, addons);
+        let resp = client.post(&url).json(&addons).send().await?;
+        log::debug!("{} {}", resp.status(), url);
+        let resp = resp.text().await?;
         let data: serde_json::Value = serde_json::from_str(&resp)?;
         let mut items = HashMap::new();
         for addon in data.as_array().unwrap().iter() {
-            match CurseForgeAPI::parse_json(addon, game_version) {
-                Some(a) => {
-                    items.insert(a.addon_id.clone(), a.clone());
-                }
-                None => {}
+            if let Some(a) = CurseForgeAPI::parse_json(addon, flavor) {
+                items.insert(a.addon_id.clone(), a.clone());
             }
         }
 
         Ok(items)
     }
+
+    /// Sends a GET request without query parameters.
+    async fn get(
+        client: &reqwest::Client,
+        url: &str,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        CurseForgeAPI::get_with_query(client, url, &[]).await
+    }
+
+    /// Sends a GET request with URL encoded query parameters, logging the
+    /// URL and the response status at debug level.
+    async fn get_with_query(
+        client: &reqwest::Client,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let request = client.get(url).query(query).build()?;
+        let url = request.url().clone();
+        log::debug!("GET {}", url);
+        let response = client.execute(request).await;
+        match &response {
+            Ok(response) => log::debug!("{} {}", response.status(), url),
+            Err(err) => log::debug!("GET {} failed: {}", url, err),
+        }
+        response
+    }
+
+    fn client(network: &Network) -> Result<reqwest::Client, reqwest::Error> {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(network.timeout))
+            .build()
+    }
 }
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Client folders inside a World of Warcraft installation as
+/// (folder, flavor name, CurseForge flavor).
+const CLIENTS: [(&str, &str, &str); 6] = [
+    ("_retail_", "Retail", "wow_retail"),
+    ("_classic_", "Tbc", "wow_burning_crusade"),
+    ("_classic_era_", "Classic", "wow_classic"),
+    ("_ptr_", "Retail PTR", "wow_retail"),
+    ("_beta_", "Beta", "wow_retail"),
+    ("_classic_ptr_", "Tbc PTR", "wow_burning_crusade"),
+];
+
+/// Default Battle.net install locations relative to `drive_c`.
+const INSTALL_DIRS: [&str; 2] = [
+    "Program Files (x86)/World of Warcraft",
+    "Program Files/World of Warcraft",
+];
+
+/// Directories (relative to the home directory) that contain one Wine prefix
+/// per subdirectory.
+const PREFIX_DIRS: [&str; 5] = [
+    "Games",
+    ".local/share/wineprefixes",
+    ".local/share/bottles/bottles",
+    ".steam/steam/steamapps/compatdata",
+    ".local/share/Steam/steamapps/compatdata",
+];
+
+pub struct Installation {
+    pub name: String,
+    pub path: String,
+    pub curse_flavor: String,
+}
+
+pub struct InstallationDetector {}
+
+impl InstallationDetector {
+    /// Scans the default Wine prefix, Lutris game directories, Bottles and
+    /// Steam/Proton prefixes for World of Warcraft clients and returns the
+    /// AddOns folder of every client that was found.
+    pub fn find_installations() -> Vec<Installation> {
+        let home = match dirs::home_dir() {
+            Some(home) => home,
+            None => return Vec::new(),
+        };
+        let mut drives = vec![home.join(".wine/drive_c")];
+        for dir in PREFIX_DIRS.iter() {
+            drives.extend(InstallationDetector::find_drives(&home.join(dir)));
+        }
+
+        let mut installations: Vec<Installation> = Vec::new();
+        for drive in drives.iter() {
+            for install_dir in INSTALL_DIRS.iter() {
+                let wow = drive.join(install_dir);
+                for (folder, name, curse_flavor) in CLIENTS.iter() {
+                    let client = wow.join(folder);
+                    if !client.is_dir() {
+                        continue;
+                    }
+                    let path = client
+                        .join("Interface/AddOns")
+                        .to_string_lossy()
+                        .to_string();
+                    if installations.iter().any(|i| i.path == path) {
+                        continue;
+                    }
+                    installations.push(Installation {
+                        name: name.to_string(),
+                        path,
+                        curse_flavor: curse_flavor.to_string(),
+                    });
+                }
+            }
+        }
+        installations
+    }
+
+    /// Returns the `drive_c` folders of all prefixes below `dir`. Proton
+    /// prefixes keep theirs in an additional `pfx` folder.
+    fn find_drives(dir: &Path) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .flat_map(|prefix| {
+                vec![prefix.join("drive_c"), prefix.join("pfx/drive_c")]
+            })
+            .filter(|drive| drive.is_dir())
+            .collect()
+    }
+}
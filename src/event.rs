@@ -7,11 +7,12 @@ use std::sync::{
 use std::thread;
 use std::time::Duration;
 
-use termion::event::Key;
+use termion::event::{Event as TermEvent, Key, MouseEvent};
 use termion::input::TermRead;
 
 pub enum Event<I> {
     Input(I),
+    Mouse(MouseEvent),
     Tick,
 }
 
@@ -52,15 +53,20 @@ impl Events {
             let ignore_exit_key = ignore_exit_key.clone();
             thread::spawn(move || {
                 let stdin = io::stdin();
-                for evt in stdin.keys() {
-                    if let Ok(key) = evt {
-                        if let Err(err) = tx.send(Event::Input(key)) {
-                            eprintln!("{}", err);
-                            return;
-                        }
-                        if !ignore_exit_key.load(Ordering::Relaxed) && key == config.exit_key {
-                            return;
-                        }
+                for event in stdin.events().flatten() {
+                    let exit = !ignore_exit_key.load(Ordering::Relaxed)
+                        && event == TermEvent::Key(config.exit_key);
+                    let event = match event {
+                        TermEvent::Key(key) => Event::Input(key),
+                        TermEvent::Mouse(mouse) => Event::Mouse(mouse),
+                        TermEvent::Unsupported(_) => continue,
+                    };
+                    if let Err(err) = tx.send(event) {
+                        eprintln!("{}", err);
+                        return;
+                    }
+                    if exit {
+                        return;
                     }
                 }
             })
@@ -0,0 +1,173 @@
+use termion::event::Key;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// A single line of text with a cursor, edited with the usual readline
+/// keys.
+#[derive(Default)]
+pub struct LineInput {
+    text: String,
+    /// Byte index of the cursor in `text`.
+    cursor: usize,
+}
+
+impl LineInput {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text and moves the cursor to its end.
+    pub fn set(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    /// Edits the text or moves the cursor. Returns false for keys that are
+    /// not handled.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        match key {
+            Key::Char(c) => {
+                self.text.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            Key::Backspace => {
+                if let Some(start) = self.previous_boundary() {
+                    self.text.drain(start..self.cursor);
+                    self.cursor = start;
+                }
+            }
+            Key::Delete => {
+                if let Some(end) = self.next_boundary() {
+                    self.text.drain(self.cursor..end);
+                }
+            }
+            Key::Left => {
+                self.cursor = self.previous_boundary().unwrap_or(self.cursor)
+            }
+            Key::Right => {
+                self.cursor = self.next_boundary().unwrap_or(self.cursor)
+            }
+            Key::Home | Key::Ctrl('a') => self.cursor = 0,
+            Key::End | Key::Ctrl('e') => self.cursor = self.text.len(),
+            Key::Ctrl('w') => {
+                let before = &self.text[..self.cursor];
+                let start = before
+                    .trim_end()
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| c.is_whitespace())
+                    .map_or(0, |(i, c)| i + c.len_utf8());
+                self.text.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            Key::Ctrl('u') => {
+                self.text.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn previous_boundary(&self) -> Option<usize> {
+        self.text[..self.cursor]
+            .char_indices()
+            .last()
+            .map(|(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> Option<usize> {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map(|c| self.cursor + c.len_utf8())
+    }
+
+    /// Returns the part of the text that fits into `width` columns with the
+    /// cursor in view, and the column of the cursor within that part.
+    pub fn visible(&self, width: u16) -> (&str, u16) {
+        let width = width as usize;
+        let mut start = 0;
+        let mut column = self.text[..self.cursor].width();
+        // Drops characters from the start until the cursor fits.
+        for c in self.text[..self.cursor].chars() {
+            if column < width {
+                break;
+            }
+            start += c.len_utf8();
+            column -= c.width().unwrap_or(0);
+        }
+        (&self.text[start..], column as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(text: &str) -> LineInput {
+        let mut input = LineInput::default();
+        input.set(text.to_string());
+        input
+    }
+
+    #[test]
+    fn ctrl_w_deletes_the_previous_word() {
+        let mut line = input("foo bar  ");
+        line.handle_key(Key::Ctrl('w'));
+        assert_eq!(line.text(), "foo ");
+        line.handle_key(Key::Ctrl('w'));
+        assert_eq!(line.text(), "");
+    }
+
+    #[test]
+    fn ctrl_w_after_multi_byte_whitespace() {
+        for text in ["foo\u{a0}bar", "foo\u{3000}bar"].iter() {
+            let mut line = input(text);
+            line.handle_key(Key::Ctrl('w'));
+            assert_eq!(line.text(), &text[..text.len() - 3]);
+            line.handle_key(Key::Ctrl('w'));
+            assert_eq!(line.text(), "");
+        }
+    }
+
+    #[test]
+    fn ctrl_u_deletes_to_the_start() {
+        let mut line = input("äöü ß");
+        line.handle_key(Key::Left);
+        line.handle_key(Key::Ctrl('u'));
+        assert_eq!(line.text(), "ß");
+        line.handle_key(Key::Char('x'));
+        assert_eq!(line.text(), "xß");
+    }
+
+    #[test]
+    fn backspace_and_delete_remove_whole_characters() {
+        let mut line = input("a日本");
+        line.handle_key(Key::Backspace);
+        assert_eq!(line.text(), "a日");
+        line.handle_key(Key::Home);
+        line.handle_key(Key::Delete);
+        assert_eq!(line.text(), "日");
+        line.handle_key(Key::End);
+        line.handle_key(Key::Backspace);
+        line.handle_key(Key::Backspace);
+        assert_eq!(line.text(), "");
+    }
+
+    #[test]
+    fn visible_keeps_the_cursor_in_view() {
+        let line = input("ab");
+        assert_eq!(line.visible(10), ("ab", 2));
+        let line = input("日本語");
+        // Every character takes two columns.
+        assert_eq!(line.visible(10), ("日本語", 6));
+        assert_eq!(line.visible(5), ("本語", 4));
+        let mut line = input("日本語");
+        line.handle_key(Key::Home);
+        assert_eq!(line.visible(3), ("日本語", 0));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::settings::{Logging, APP_DIR};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::env;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const FILENAME: &str = "wowAddonManager.log";
+/// Only HTTP details from these crates are logged at debug level, the other
+/// dependencies are too noisy.
+const DEBUG_TARGETS: [&str; 2] = ["wowAddonManager", "reqwest"];
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// Maximum size in bytes before the file is rotated.
+    max_size: u64,
+    /// Number of rotated files that are kept.
+    max_files: usize,
+}
+
+/// Writes log records with a timestamp to a file in the state directory and
+/// rotates it when it gets too large.
+struct FileLogger {
+    file: Mutex<Option<LogFile>>,
+}
+
+static LOGGER: FileLogger = FileLogger {
+    file: Mutex::new(None),
+};
+
+/// Opens the log file and installs the logger. Returns the path of the log
+/// file.
+pub fn init(config: &Logging) -> Result<PathBuf, Box<dyn Error>> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let size = file.metadata()?.len();
+    *LOGGER.file.lock().unwrap() = Some(LogFile {
+        path: path.clone(),
+        file,
+        size,
+        max_size: 0,
+        max_files: 0,
+    });
+    log::set_logger(&LOGGER)?;
+    configure(config);
+    Ok(path)
+}
+
+/// Applies changed log settings.
+pub fn configure(config: &Logging) {
+    log::set_max_level(if config.debug {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    });
+    if let Some(file) = LOGGER.file.lock().unwrap().as_mut() {
+        file.max_size = config.max_size * 1024;
+        file.max_files = config.max_files;
+    }
+}
+
+/// The log file in `$XDG_STATE_HOME/wowAddonManager`, which defaults to
+/// `~/.local/state/wowAddonManager`.
+pub fn path() -> Result<PathBuf, Box<dyn Error>> {
+    let state_dir = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match dirs::home_dir() {
+            Some(home) => home.join(".local").join("state"),
+            None => return Err("State directory not found!".into()),
+        },
+    };
+    Ok(state_dir.join(APP_DIR).join(FILENAME))
+}
+
+/// The current local time as written to the log.
+pub fn timestamp() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Returns the level of a line written by the logger. Lines of multi-line
+/// messages after the first have no level.
+pub fn line_level(line: &str) -> Option<Level> {
+    let rest = line.get(19..)?.strip_prefix(" [")?;
+    let end = rest.find(']')?;
+    rest[..end].trim().parse().ok()
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+            && (metadata.level() <= Level::Warn
+                || DEBUG_TARGETS
+                    .iter()
+                    .any(|t| metadata.target().starts_with(t)))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} [{:<5}] {}\n",
+            timestamp(),
+            record.level(),
+            record.args()
+        );
+        let mut file = self.file.lock().unwrap();
+        if let Some(file) = file.as_mut() {
+            if file.max_size > 0
+                && file.size + line.len() as u64 > file.max_size
+            {
+                // Logging must never take the app down, so a failed rotation
+                // just keeps writing to the current file.
+                let _ = file.rotate();
+            }
+            if file.file.write_all(line.as_bytes()).is_ok() {
+                file.size += line.len() as u64;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+impl LogFile {
+    /// Renames the log file to `.1`, shifting older files up and deleting
+    /// the oldest, and starts a new file.
+    fn rotate(&mut self) -> Result<(), Box<dyn Error>> {
+        let rotated = |n: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", n));
+            PathBuf::from(path)
+        };
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(self.max_files));
+            for n in (1..self.max_files).rev() {
+                if rotated(n).exists() {
+                    fs::rename(rotated(n), rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
@@ -1,31 +1,44 @@
+mod action;
 mod addon_manager;
 mod app;
 mod curse;
+mod detect;
 #[allow(dead_code)]
 mod event;
+mod input;
+mod logger;
+mod operations;
+mod palette;
+mod picker;
+mod search_history;
 mod settings;
+mod setup;
+mod theme;
 
 use crate::addon_manager::AddonManager;
 use crate::app::{App, LogLevel, Mode};
 use crate::event::{Event, Events};
 use crate::settings::Settings;
 use std::{
+    env,
     error::Error,
     io::{self},
 };
 use termion::{
-    event::Key, input::MouseTerminal, raw::IntoRawMode,
-    screen::AlternateScreen,
-};
-use tui::{
-    backend::TermionBackend,
-    Terminal,
+    event::Key, input::MouseTerminal, raw::IntoRawMode, screen::AlternateScreen,
 };
+use tui::{backend::TermionBackend, Terminal};
 extern crate config;
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // `wowAddonManager history [filter]` prints the operation history.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("history") {
+        return operations::print(&args[1..].join(" "));
+    }
+
     // Terminal initialization
-    let settings = Settings::new();
+    let (settings, diagnostics) = Settings::new();
     let stdout = io::stdout().into_raw_mode()?;
     let stdout = MouseTerminal::from(stdout);
     let stdout = AlternateScreen::from(stdout);
@@ -35,129 +48,97 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Setup event handlers
     let mut events = Events::new();
+    // Quitting is a regular action, so the input thread must never stop on
+    // its own.
+    events.disable_exit_key();
 
-    let mut app = App::new(
-        settings.paths.classic.clone(),
-        settings.paths.retail.clone(),
-        settings.paths.tbc.clone(),
-    );
-
-    match AddonManager::init_addon_db(&settings.paths.classic) {
-        Ok(_) => app.log(
-            "Classic addon directory successfully loaded.\n".to_string(),
-            LogLevel::Info,
-        ),
-        Err(err) => app.log(
-            format!("Couldn't load classic addon directory.\n{}\n", err),
-            LogLevel::Error,
-        ),
+    // The logger has to be ready before the app logs anything.
+    let log_error = logger::init(&settings.log).err();
+    let mut app = App::new(settings);
+    if let Some(err) = log_error {
+        app.log(
+            format!("Couldn't open the log file.\n{}\n", err),
+            LogLevel::Warning,
+        );
     }
 
-    match AddonManager::init_addon_db(&settings.paths.retail) {
-        Ok(_) => app.log(
-            "Retail addon directory successfully loaded.\n".to_string(),
-            LogLevel::Info,
-        ),
-        Err(err) => app.log(
-            format!("Couldn't load retail addon directory.\n{}\n", err),
-            LogLevel::Error,
-        ),
+    for flavor in app.settings.flavors.clone().iter() {
+        if flavor.path.is_empty() {
+            continue;
+        }
+        match AddonManager::init_addon_db(&flavor.path) {
+            Ok(_) => app.log(
+                format!(
+                    "{} addon directory successfully loaded.\n",
+                    flavor.name
+                ),
+                LogLevel::Info,
+            ),
+            Err(err) => app.log(
+                format!(
+                    "Couldn't load {} addon directory.\n{}\n",
+                    flavor.name, err
+                ),
+                LogLevel::Error,
+            ),
+        }
     }
 
-    let mut dialog_callback: Option<fn(app: &mut App)> = None;
+    if app.settings.flavors.iter().all(|f| f.path.is_empty()) {
+        app.start_setup();
+    }
+    if !diagnostics.is_empty() {
+        app.show_config_diagnostics(&diagnostics);
+    } else if app.settings.flavors.iter().any(|f| f.path.is_empty())
+        && !matches!(app.mode, Mode::Setup)
+    {
+        app.detect_installations();
+    }
 
     loop {
         terminal.draw(|mut f| {
             app.draw_app(&mut f);
         })?;
-
-        // write!(
-        //     terminal.backend_mut(),
-        //     "{}",
-        //     Goto(3 + app.userInput.width() as u16, 3)
-        // )?;
-        if let Event::Input(input) = events.next()? {
-            match app.mode {
-                Mode::Normal => {
-                    if input == settings.key_bindings.next_tab {
-                        app.select_next_tab();
-                    } else if input == settings.key_bindings.prev_tab {
-                        app.select_prev_tab();
-                    // app.mode = Mode::Editing;
-                    // terminal.show_cursor()?;
-                    // events.disable_exit_key();
-                    } else if input == settings.key_bindings.download_addon {
-                        app.download();
-                    } else if input == settings.key_bindings.remove_addon {
-                        dialog_callback = Some(App::remove_addon);
-                        app.add_dialog(
-                            "Do you want to delete this addon?".to_string(),
-                            true,
-                        );
-                    } else if input == settings.key_bindings.search_addon {
-                        // app.select_search_box();
-                        app.select_search();
-                        // terminal.show_cursor()?;
-                        events.disable_exit_key();
-                    } else if input == settings.key_bindings.next_table_item {
-                        app.next_table_item();
-                    } else if input == settings.key_bindings.prev_table_item {
-                        app.prev_table_item();
-                    } else if input
-                        == settings.key_bindings.select_classic_version
-                    {
-                        app.select_classic();
-                    } else if input
-                        == settings.key_bindings.select_retail_version
-                    {
-                        app.select_retail();
-                    } else if input
-                        == settings.key_bindings.select_tbc_version
-                    {
-                        app.select_tbc();
-                    } else if input == settings.key_bindings.scroll_down_log {
-                        app.scroll_down_log();
-                    } else if input == settings.key_bindings.scroll_up_log {
-                        app.scroll_up_log();
-                    } else if input == settings.key_bindings.quit {
-                        break;
-                    } else if input == settings.key_bindings.update_all_addons {
-                        app.update_all();
-                    } else if input == settings.key_bindings.update_addon {
-                        app.update_addon();
-                    }
-                }
-                Mode::Editing => match input {
-                    Key::Char('\n') => {
-                        app.mode = Mode::Normal;
-                        app.search(app.user_input.clone());
-                    }
+        match app.cursor {
+            Some((x, y)) => {
+                terminal.show_cursor()?;
+                terminal.set_cursor(x, y)?;
+            }
+            None => terminal.hide_cursor()?,
+        }
+        match events.next()? {
+            Event::Input(input) => match app.mode {
+                Mode::Normal => app.handle_key(input),
+                Mode::Editing => app.handle_search_key(input),
+                Mode::SavingSearch => app.handle_save_search_key(input),
+                Mode::EditingSetting => match input {
+                    Key::Char('\n') => app.apply_setting(),
                     Key::Char(c) => {
-                        app.user_input.push(c);
+                        app.setting_input.push(c);
                     }
                     Key::Backspace => {
-                        app.user_input.pop();
+                        app.setting_input.pop();
                     }
                     Key::Esc => {
                         app.mode = Mode::Normal;
-                        terminal.hide_cursor()?;
-                        events.enable_exit_key();
                     }
                     _ => {}
                 },
+                Mode::Setup => app.handle_setup_key(input),
+                Mode::Filtering => app.handle_filter_key(input),
+                Mode::Palette => app.handle_palette_key(input),
+                Mode::Popup => app.handle_popup_key(input),
+                Mode::Picker => app.handle_picker_key(input),
                 Mode::Dialog => match input {
-                    Key::Char('y') | Key::Char('Y') => {
-                        match dialog_callback {
-                            Some(callback) => callback(&mut app),
-                            None => (),
-                        }
-                        app.stop_dialog();
-                    }
-                    _ => {
-                        app.stop_dialog();
-                    }
+                    Key::Char('y') | Key::Char('Y') => app.confirm_dialog(),
+                    _ => app.stop_dialog(),
                 },
-            }
+            },
+            Event::Mouse(mouse) => app.handle_mouse(mouse),
+            Event::Tick => app.on_tick(),
+        }
+        if app.should_quit {
+            break;
         }
     }
     Ok(())
@@ -0,0 +1,144 @@
+use crate::logger;
+use crate::settings::Settings;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+const FILENAME: &str = "operations.jsonl";
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationKind {
+    Install,
+    Update,
+    Reinstall,
+    Remove,
+}
+
+impl OperationKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            OperationKind::Install => "install",
+            OperationKind::Update => "update",
+            OperationKind::Reinstall => "reinstall",
+            OperationKind::Remove => "remove",
+        }
+    }
+}
+
+/// An install, update or removal of an addon.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub time: String,
+    pub user: String,
+    pub kind: OperationKind,
+    pub flavor: String,
+    pub addon_id: String,
+    pub addon: String,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    /// The error if the operation failed.
+    pub error: Option<String>,
+}
+
+impl Operation {
+    /// Creates an operation done now by the current user.
+    pub fn new(
+        kind: OperationKind,
+        flavor: &str,
+        addon_id: &str,
+        addon: &str,
+    ) -> Operation {
+        Operation {
+            time: logger::timestamp(),
+            user: env::var("USER")
+                .or_else(|_| env::var("LOGNAME"))
+                .unwrap_or_default(),
+            kind,
+            flavor: flavor.to_string(),
+            addon_id: addon_id.to_string(),
+            addon: addon.to_string(),
+            from_version: None,
+            to_version: None,
+            error: None,
+        }
+    }
+
+    /// "from -> to", or a single version for installs and removals.
+    pub fn versions(&self) -> String {
+        match (&self.from_version, &self.to_version) {
+            (Some(from), Some(to)) => format!("{} -> {}", from, to),
+            (Some(version), None) | (None, Some(version)) => version.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    pub fn result(&self) -> &str {
+        self.error.as_deref().unwrap_or("ok")
+    }
+
+    /// Whether every word of `filter` is part of the time, user, action,
+    /// flavor, addon, a version or the result, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let fields = [
+            self.time.as_str(),
+            self.user.as_str(),
+            self.kind.name(),
+            self.flavor.as_str(),
+            self.addon.as_str(),
+            &self.versions(),
+            self.result(),
+        ]
+        .join(" ")
+        .to_lowercase();
+        filter
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| fields.contains(word))
+    }
+}
+
+/// Appends an operation to the history file in the data directory. Entries
+/// are never changed or removed.
+pub fn append(operation: &Operation) -> Result<(), Box<dyn Error>> {
+    let path = Settings::data_file(FILENAME)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(operation)?)?;
+    Ok(())
+}
+
+/// Loads all operations, oldest first. Lines that can't be parsed, e.g.
+/// from an interrupted write, are skipped.
+pub fn load() -> Result<Vec<Operation>, Box<dyn Error>> {
+    let path = Settings::data_file(FILENAME)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Prints the operations matching `filter` for the `history` command.
+pub fn print(filter: &str) -> Result<(), Box<dyn Error>> {
+    for operation in load()?.iter().filter(|o| o.matches(filter)) {
+        println!(
+            "{}  {:<10} {:<10} {:<9} {:<30} {:<30} {}",
+            operation.time,
+            operation.user,
+            operation.flavor,
+            operation.kind.name(),
+            operation.addon,
+            operation.versions(),
+            operation.result()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::action::{Action, ACTIONS};
+use crate::picker::{Picker, PickerItem, PickerResult};
+use crate::settings::Settings;
+use crate::theme::Theme;
+use termion::event::Key;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::Frame;
+
+pub enum PaletteResult {
+    Continue,
+    Run(Action),
+    Cancelled,
+}
+
+/// A command palette that fuzzy searches all actions by their description
+/// or config name.
+pub struct CommandPalette {
+    picker: Picker,
+    /// The action of every item of the picker.
+    actions: Vec<Action>,
+}
+
+impl CommandPalette {
+    pub fn new(settings: &Settings) -> CommandPalette {
+        let flavors = &settings.flavors;
+        let actions: Vec<(Action, &str, String)> = ACTIONS
+            .iter()
+            .filter(|info| info.action != Action::CommandPalette)
+            .map(|info| {
+                (
+                    info.action,
+                    info.name,
+                    Settings::sequences_to_string(
+                        settings.key_bindings.get(info.action),
+                    ),
+                )
+            })
+            .chain(flavors.iter().enumerate().map(|(index, flavor)| {
+                (
+                    Action::SelectFlavor(index),
+                    "select_flavor",
+                    Settings::key_to_string(flavor.key),
+                )
+            }))
+            .collect();
+        let items = actions
+            .iter()
+            .map(|(action, name, keys)| {
+                let description = action.description(flavors);
+                PickerItem {
+                    search: vec![description.clone(), name.to_string()],
+                    cells: vec![description, keys.clone()],
+                }
+            })
+            .collect();
+        CommandPalette {
+            picker: Picker::with_columns(
+                "Command",
+                &[("Action", 70), ("Key", 30)],
+                items,
+            )
+            .prompt(":"),
+            actions: actions.into_iter().map(|(action, _, _)| action).collect(),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> PaletteResult {
+        match self.picker.handle_key(key) {
+            PickerResult::Continue => PaletteResult::Continue,
+            PickerResult::Select(index) => {
+                PaletteResult::Run(self.actions[index])
+            }
+            PickerResult::Cancelled => PaletteResult::Cancelled,
+        }
+    }
+
+    pub fn draw<B>(&mut self, frame: &mut Frame<B>, area: Rect, theme: &Theme)
+    where
+        B: Backend,
+    {
+        self.picker.draw(frame, area, theme);
+    }
+}
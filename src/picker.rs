@@ -0,0 +1,186 @@
+use crate::theme::Theme;
+use termion::event::Key;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::widgets::{
+    Block, Borders, Clear, Paragraph, Row, Table, TableState, Text,
+};
+use tui::Frame;
+
+pub enum PickerResult {
+    Continue,
+    /// The index of the picked item.
+    Select(usize),
+    Cancelled,
+}
+
+pub struct PickerItem {
+    /// The text of every column.
+    pub cells: Vec<String>,
+    /// The texts the input is matched against. They don't have to be shown.
+    pub search: Vec<String>,
+}
+
+/// A list to pick an item from, like an action, a category or a saved
+/// search. The list can be narrowed down by typing.
+pub struct Picker {
+    title: String,
+    /// Shown in front of the input.
+    prompt: &'static str,
+    headers: Vec<&'static str>,
+    widths: Vec<Constraint>,
+    items: Vec<PickerItem>,
+    input: String,
+    state: TableState,
+}
+
+impl Picker {
+    /// A picker with a single column of names.
+    pub fn new(title: &str, items: Vec<String>) -> Picker {
+        let items = items
+            .into_iter()
+            .map(|name| PickerItem {
+                search: vec![name.clone()],
+                cells: vec![name],
+            })
+            .collect();
+        Picker::with_columns(title, &[("Name", 100)], items)
+    }
+
+    /// A picker with the given headers and column widths in percent.
+    pub fn with_columns(
+        title: &str,
+        columns: &[(&'static str, u16)],
+        items: Vec<PickerItem>,
+    ) -> Picker {
+        let mut state = TableState::default();
+        state.select(Some(0));
+        Picker {
+            title: title.to_string(),
+            prompt: "",
+            headers: columns.iter().map(|(header, _)| *header).collect(),
+            widths: columns
+                .iter()
+                .map(|(_, width)| Constraint::Percentage(*width))
+                .collect(),
+            items,
+            input: String::new(),
+            state,
+        }
+    }
+
+    pub fn prompt(mut self, prompt: &'static str) -> Picker {
+        self.prompt = prompt;
+        self
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> PickerResult {
+        let len = self.matches().len();
+        let selected = self.state.selected().unwrap_or(0);
+        match key {
+            Key::Char('\n') => match self.matches().get(selected) {
+                Some(&index) => PickerResult::Select(index),
+                None => PickerResult::Continue,
+            },
+            Key::Esc => PickerResult::Cancelled,
+            Key::Down | Key::Ctrl('n') => {
+                if selected + 1 < len {
+                    self.state.select(Some(selected + 1));
+                }
+                PickerResult::Continue
+            }
+            Key::Up | Key::Ctrl('p') => {
+                self.state.select(Some(selected.saturating_sub(1)));
+                PickerResult::Continue
+            }
+            Key::Char(c) => {
+                self.input.push(c);
+                self.state.select(Some(0));
+                PickerResult::Continue
+            }
+            Key::Backspace => {
+                self.input.pop();
+                self.state.select(Some(0));
+                PickerResult::Continue
+            }
+            _ => PickerResult::Continue,
+        }
+    }
+
+    /// Returns the indices of the items matching the input, best matches
+    /// first.
+    fn matches(&self) -> Vec<usize> {
+        let mut matches: Vec<(i32, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| {
+                let score = item
+                    .search
+                    .iter()
+                    .filter_map(|text| fuzzy_score(&self.input, text))
+                    .max()?;
+                Some((score, i))
+            })
+            .collect();
+        // The sort is stable, so equal scores keep the order of the items.
+        matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+        matches.into_iter().map(|(_, i)| i).collect()
+    }
+
+    pub fn draw<B>(&mut self, frame: &mut Frame<B>, area: Rect, theme: &Theme)
+    where
+        B: Backend,
+    {
+        frame.render_widget(Clear, area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(area);
+        let input = [Text::raw(format!("{}{}", self.prompt, self.input))];
+        let input = Paragraph::new(input.iter()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme.active)
+                .title(self.title.as_str()),
+        );
+        frame.render_widget(input, chunks[0]);
+
+        let matches = self.matches();
+        let items = &self.items;
+        let rows = matches
+            .iter()
+            .map(|&i| Row::StyledData(items[i].cells.iter(), theme.default));
+        let table = Table::new(self.headers.iter(), rows)
+            .block(Block::default().borders(Borders::ALL))
+            .header_style(theme.active)
+            .widths(&self.widths)
+            .style(theme.default)
+            .highlight_style(theme.hover)
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(table, chunks[1], &mut self.state);
+    }
+}
+
+/// Scores how well `query` matches `text`. All characters of the query have
+/// to appear in `text` in the same order; matches at the start of a word and
+/// consecutive matches score higher. Whitespace in the query is ignored.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = (start..text.len()).find(|&i| text[i] == c)?;
+        score += 1;
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        if index > 0 && previous == Some(index - 1) {
+            score += 2;
+        }
+        previous = Some(index);
+        start = index + 1;
+    }
+    Some(score)
+}
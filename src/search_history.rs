@@ -0,0 +1,72 @@
+use crate::settings::Settings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+const FILENAME: &str = "search_history.json";
+/// Number of recent queries kept per flavor.
+const MAX_RECENT: usize = 50;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub query: String,
+}
+
+/// Recent and saved searches, stored in the data directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SearchHistory {
+    /// Recent queries by flavor name, oldest first.
+    #[serde(default)]
+    recent: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub saved: Vec<SavedQuery>,
+}
+
+impl SearchHistory {
+    /// Loads the history. A missing file is an empty history.
+    pub fn load() -> Result<SearchHistory, Box<dyn Error>> {
+        let path = Settings::data_file(FILENAME)?;
+        if !path.exists() {
+            return Ok(SearchHistory::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Settings::data_file(FILENAME)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn recent(&self, flavor: &str) -> &[String] {
+        self.recent.get(flavor).map_or(&[], |r| r.as_slice())
+    }
+
+    /// Adds `query` as the newest query of `flavor`.
+    pub fn add(&mut self, flavor: &str, query: &str) {
+        let recent = self.recent.entry(flavor.to_string()).or_default();
+        recent.retain(|q| q != query);
+        recent.push(query.to_string());
+        if recent.len() > MAX_RECENT {
+            recent.remove(0);
+        }
+    }
+
+    /// Saves `query` as `name`, replacing a saved query with the same name.
+    pub fn save_query(&mut self, name: &str, query: &str) {
+        let saved = SavedQuery {
+            name: name.to_string(),
+            query: query.to_string(),
+        };
+        match self.saved.iter_mut().find(|q| q.name == name) {
+            Some(existing) => *existing = saved,
+            None => self.saved.push(saved),
+        }
+    }
+}
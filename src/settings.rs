@@ -1,144 +1,878 @@
 extern crate config;
 extern crate dirs;
 
-use std::{error::Error, fs, path::Path};
+use crate::action::{Action, ACTIONS};
+use crate::curse::BASE_URL;
+use crate::theme::{StyleConfig, Theme, PRESETS, STYLE_NAMES};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 use termion::event::Key;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, Value};
 
-pub struct Paths {
-    pub classic: String,
-    pub retail: String,
-    pub tbc: String,
+/// A game flavor with its own AddOns directory, e.g. Retail, Classic Era or a
+/// PTR installation.
+#[derive(Clone)]
+pub struct Flavor {
+    pub name: String,
+    pub path: String,
+    /// The `gameVersionFlavor` used by CurseForge for this flavor.
+    pub curse_flavor: String,
+    pub key: Key,
 }
 
+#[derive(Deserialize)]
+struct FlavorConfig {
+    name: String,
+    #[serde(default)]
+    path: String,
+    curse_flavor: String,
+    #[serde(default)]
+    key: String,
+}
+
+/// Flavors of configs written before `[[flavors]]` existed, as
+/// (name, path key, CurseForge flavor, keybinding key).
+const LEGACY_FLAVORS: [(&str, &str, &str, &str); 3] = [
+    (
+        "Classic",
+        "classic",
+        "wow_classic",
+        "select_classic_version",
+    ),
+    ("Tbc", "tbc", "wow_burning_crusade", "select_tbc_version"),
+    ("Retail", "retail", "wow_retail", "select_retail_version"),
+];
+
+/// Keys that have to be pressed one after another, e.g. `g g`.
+pub type KeySequence = Vec<Key>;
+
+/// The key sequences bound to each action, in the order of `ACTIONS`.
 pub struct KeyBindings {
-    pub update_addon: Key,
-    pub update_all_addons: Key,
-    pub remove_addon: Key,
-    pub download_addon: Key,
-    pub install_addon: Key,
-    pub select_retail_version: Key,
-    pub select_classic_version: Key,
-    pub select_tbc_version: Key,
-    pub search_addon: Key,
-    pub next_tab: Key,
-    pub prev_tab: Key,
-    pub next_table_item: Key,
-    pub prev_table_item: Key,
-    pub quit: Key,
-    pub scroll_down_log: Key,
-    pub scroll_up_log: Key,
+    bindings: Vec<(Action, Vec<KeySequence>)>,
+}
+
+impl KeyBindings {
+    pub fn list(&self) -> &[(Action, Vec<KeySequence>)] {
+        &self.bindings
+    }
+
+    pub fn get(&self, action: Action) -> &[KeySequence] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, sequences)| sequences)
+    }
+
+    pub fn set(&mut self, action: Action, sequences: Vec<KeySequence>) {
+        if let Some(binding) =
+            self.bindings.iter_mut().find(|(a, _)| *a == action)
+        {
+            binding.1 = sequences;
+        }
+    }
+}
+
+/// The result of looking up the keys pressed so far.
+pub enum KeyMatch {
+    /// No binding starts with the keys.
+    None,
+    /// The keys are the start of at least one longer sequence.
+    Prefix,
+    /// The keys are bound to an action, but also start a longer sequence.
+    Ambiguous(Action),
+    Exact(Action),
+}
+
+#[derive(Clone)]
+pub struct Network {
+    pub base_url: String,
+    /// Request timeout in seconds.
+    pub timeout: u64,
+}
+
+const DEFAULT_TIMEOUT: u64 = 30;
+
+/// The `[log]` section.
+#[derive(Clone, PartialEq)]
+pub struct Logging {
+    /// Also logs HTTP requests.
+    pub debug: bool,
+    /// Size of the log file in KiB before it is rotated.
+    pub max_size: u64,
+    /// Number of rotated log files that are kept.
+    pub max_files: usize,
+}
+
+const DEFAULT_LOG_SIZE: u64 = 1024;
+const DEFAULT_LOG_FILES: usize = 3;
+
+/// A single editable value of the settings tab.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SettingField {
+    FlavorPath(usize),
+    FlavorKey(usize),
+    KeyBinding(Action),
+    BaseUrl,
+    Timeout,
+    LogDebug,
+}
+
+pub struct SettingEntry {
+    pub field: SettingField,
+    pub section: String,
+    pub name: String,
+    pub value: String,
 }
 
 const FILENAME: &str = "Config.toml";
-const APP_DIR: &str = "wowAddonManager";
+const DEFAULT_CONFIG: &str = include_str!("../Config.toml");
+const FLAVOR_KEYS: [&str; 4] = ["name", "path", "curse_flavor", "key"];
+/// The directory of the app in the config, data and state directories.
+pub const APP_DIR: &str = "wowAddonManager";
 
 pub struct Settings {
-    pub paths: Paths,
+    pub flavors: Vec<Flavor>,
     pub key_bindings: KeyBindings,
+    pub network: Network,
+    pub log: Logging,
+    pub theme: Theme,
+    pub config_path: String,
+    /// The modification time of the config file when it was last read or
+    /// written.
+    modified: Option<SystemTime>,
 }
 
 impl Settings {
-    pub fn new() -> Settings {
+    /// Loads the config file. Problems with the config are returned as
+    /// human readable diagnostics instead of aborting; invalid values fall
+    /// back to their defaults.
+    pub fn new() -> (Settings, Vec<String>) {
+        let mut diagnostics = Vec::new();
+        let config_path = match Settings::init_config_file() {
+            Ok(path) => path,
+            Err(err) => {
+                diagnostics.push(format!(
+                    "Couldn't create {} in the config directory: {}",
+                    FILENAME, err
+                ));
+                FILENAME.to_string()
+            }
+        };
         let mut s = config::Config::default();
-        match Settings::init_config_file() {
-            Ok(path) => {
-                s.merge(config::File::with_name(&path)).unwrap();
+        if let Err(err) = s.merge(config::File::with_name(&config_path)) {
+            diagnostics.push(format!(
+                "Couldn't read {}, using the default config instead: {}",
+                config_path, err
+            ));
+            s = config::Config::default();
+            s.merge(config::File::from_str(
+                DEFAULT_CONFIG,
+                config::FileFormat::Toml,
+            ))
+            .unwrap();
+        }
+        let settings = Settings::from_config(&s, config_path, &mut diagnostics);
+        (settings, diagnostics)
+    }
+
+    /// Reads the config file again. Unlike `new` this fails if the file
+    /// can't be read, so a half written file doesn't replace the current
+    /// settings with the defaults.
+    pub fn reload(&mut self) -> Result<(Settings, Vec<String>), String> {
+        self.modified = Settings::modified(&self.config_path);
+        let mut s = config::Config::default();
+        s.merge(config::File::with_name(&self.config_path))
+            .map_err(|err| err.to_string())?;
+        let mut diagnostics = Vec::new();
+        let settings = Settings::from_config(
+            &s,
+            self.config_path.clone(),
+            &mut diagnostics,
+        );
+        Ok((settings, diagnostics))
+    }
+
+    /// Returns `true` if the config file was modified since it was last read
+    /// or written by the addon manager.
+    pub fn config_changed(&self) -> bool {
+        Settings::modified(&self.config_path) != self.modified
+    }
+
+    /// Describes what differs between `old` and these settings.
+    pub fn changes_from(&self, old: &Settings) -> Vec<String> {
+        let mut changes = Vec::new();
+        for flavor in self.flavors.iter() {
+            match old.flavors.iter().find(|f| f.name == flavor.name) {
+                Some(old) => {
+                    if old.path != flavor.path {
+                        changes.push(format!(
+                            "{} path changed to \"{}\".",
+                            flavor.name, flavor.path
+                        ));
+                    }
+                    if old.curse_flavor != flavor.curse_flavor {
+                        changes.push(format!(
+                            "{} CurseForge flavor changed to {}.",
+                            flavor.name, flavor.curse_flavor
+                        ));
+                    }
+                    if old.key != flavor.key {
+                        changes.push(format!(
+                            "select {} changed to \"{}\".",
+                            flavor.name,
+                            Settings::key_to_string(flavor.key)
+                        ));
+                    }
+                }
+                None => changes.push(format!("Added {}.", flavor.name)),
+            }
+        }
+        for flavor in old.flavors.iter() {
+            if !self.flavors.iter().any(|f| f.name == flavor.name) {
+                changes.push(format!("Removed {}.", flavor.name));
             }
-            Err(_) => {
-                s.merge(config::File::with_name("Config")).unwrap();
+        }
+        for (action, sequences) in self.key_bindings.list() {
+            if old.key_bindings.get(*action) != sequences.as_slice() {
+                changes.push(format!(
+                    "{} changed to \"{}\".",
+                    action.name(),
+                    Settings::sequences_to_string(sequences)
+                ));
             }
-        };
+        }
+        if old.network.base_url != self.network.base_url {
+            changes.push(format!(
+                "base_url changed to {}.",
+                self.network.base_url
+            ));
+        }
+        if old.network.timeout != self.network.timeout {
+            changes.push(format!(
+                "timeout changed to {} seconds.",
+                self.network.timeout
+            ));
+        }
+        if old.log != self.log {
+            changes.push(format!(
+                "Logging changed to debug = {}, max_size = {} KiB, \
+                 max_files = {}.",
+                self.log.debug, self.log.max_size, self.log.max_files
+            ));
+        }
+        if old.theme != self.theme {
+            changes.push("Theme changed.".to_string());
+        }
+        changes
+    }
+
+    fn modified(path: &str) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
 
-        let paths = Paths {
-            classic: s.get::<String>("paths.classic").unwrap_or("".to_string()),
-            retail: s.get::<String>("paths.retail").unwrap_or("".to_string()),
-            tbc: s.get::<String>("paths.tbc").unwrap_or("".to_string()),
+    fn from_config(
+        s: &config::Config,
+        config_path: String,
+        diagnostics: &mut Vec<String>,
+    ) -> Settings {
+        let mut defaults = config::Config::default();
+        defaults
+            .merge(config::File::from_str(
+                DEFAULT_CONFIG,
+                config::FileFormat::Toml,
+            ))
+            .unwrap();
+
+        let flavors = Settings::parse_flavors(s, diagnostics);
+        let key_bindings = KeyBindings {
+            bindings: ACTIONS
+                .iter()
+                .map(|info| {
+                    let sequences = Settings::parse_binding(
+                        s,
+                        &defaults,
+                        info.name,
+                        diagnostics,
+                    );
+                    (info.action, sequences)
+                })
+                .collect(),
         };
-        let update_addon = Settings::parse_key(
-            s.get::<String>("keybindings.update_addon")
-                .unwrap_or("".to_string()),
-        );
-        let update_all_addons = Settings::parse_key(
-            s.get::<String>("keybindings.update_all_addons")
-                .unwrap_or("".to_string()),
-        );
-        let remove_addon = Settings::parse_key(
-            s.get::<String>("keybindings.remove_addon")
-                .unwrap_or("".to_string()),
-        );
-        let download_addon = Settings::parse_key(
-            s.get::<String>("keybindings.download_addon")
-                .unwrap_or("".to_string()),
-        );
-        let install_addon = Settings::parse_key(
-            s.get::<String>("keybindings.install_addon")
-                .unwrap_or("".to_string()),
-        );
-        let select_retail_version = Settings::parse_key(
-            s.get::<String>("keybindings.select_retail_version")
-                .unwrap_or("".to_string()),
-        );
-        let select_classic_version = Settings::parse_key(
-            s.get::<String>("keybindings.select_classic_version")
-                .unwrap_or("".to_string()),
-        );
-        let select_tbc_version = Settings::parse_key(
-            s.get::<String>("keybindings.select_tbc_version")
-                .unwrap_or("".to_string()),
-        );
-        let search_addon = Settings::parse_key(
-            s.get::<String>("keybindings.search_addon")
-                .unwrap_or("".to_string()),
-        );
-        let next_tab = Settings::parse_key(
-            s.get::<String>("keybindings.next_tab")
-                .unwrap_or("".to_string()),
-        );
-        let prev_tab = Settings::parse_key(
-            s.get::<String>("keybindings.prev_tab")
-                .unwrap_or("".to_string()),
-        );
-        let next_table_item = Settings::parse_key(
-            s.get::<String>("keybindings.next_table_item")
-                .unwrap_or("".to_string()),
-        );
-        let prev_table_item = Settings::parse_key(
-            s.get::<String>("keybindings.prev_table_item")
-                .unwrap_or("".to_string()),
-        );
-        let quit = Settings::parse_key(
-            s.get::<String>("keybindings.quit")
-                .unwrap_or("".to_string()),
+        Settings::check_unknown_keys(s, diagnostics);
+        let network = Network {
+            base_url: s
+                .get::<String>("network.base_url")
+                .unwrap_or(BASE_URL.to_string()),
+            timeout: match s.get::<u64>("network.timeout") {
+                Ok(timeout) if timeout > 0 => timeout,
+                Err(config::ConfigError::NotFound(_)) => DEFAULT_TIMEOUT,
+                _ => {
+                    diagnostics.push(format!(
+                        "network.timeout must be a positive number of seconds, using {}.",
+                        DEFAULT_TIMEOUT
+                    ));
+                    DEFAULT_TIMEOUT
+                }
+            },
+        };
+        let log = Logging {
+            debug: match s.get::<bool>("log.debug") {
+                Ok(debug) => debug,
+                Err(config::ConfigError::NotFound(_)) => false,
+                _ => {
+                    diagnostics.push(
+                        "log.debug must be true or false, using false."
+                            .to_string(),
+                    );
+                    false
+                }
+            },
+            max_size: match s.get::<u64>("log.max_size") {
+                Ok(size) if size > 0 => size,
+                Err(config::ConfigError::NotFound(_)) => DEFAULT_LOG_SIZE,
+                _ => {
+                    diagnostics.push(format!(
+                        "log.max_size must be a positive number of KiB, using {}.",
+                        DEFAULT_LOG_SIZE
+                    ));
+                    DEFAULT_LOG_SIZE
+                }
+            },
+            max_files: match s.get::<usize>("log.max_files") {
+                Ok(files) => files,
+                Err(config::ConfigError::NotFound(_)) => DEFAULT_LOG_FILES,
+                _ => {
+                    diagnostics.push(format!(
+                        "log.max_files must be a number, using {}.",
+                        DEFAULT_LOG_FILES
+                    ));
+                    DEFAULT_LOG_FILES
+                }
+            },
+        };
+        let settings = Settings {
+            flavors,
+            key_bindings,
+            network,
+            log,
+            theme: Settings::parse_theme(s, diagnostics),
+            modified: Settings::modified(&config_path),
+            config_path,
+        };
+        settings.check_duplicate_keys(diagnostics);
+        settings.check_paths(diagnostics);
+        settings
+    }
+
+    /// Builds the theme from its preset and the styles overridden in the
+    /// `[theme]` section.
+    fn parse_theme(s: &config::Config, diagnostics: &mut Vec<String>) -> Theme {
+        let preset = s
+            .get::<String>("theme.preset")
+            .unwrap_or_else(|_| PRESETS[0].to_string());
+        let mut theme = Theme::preset(&preset).unwrap_or_else(|| {
+            diagnostics.push(format!(
+                "theme.preset: \"{}\" is not a preset, use one of {}.",
+                preset,
+                PRESETS.join(", ")
+            ));
+            Theme::dark()
+        });
+        for name in STYLE_NAMES.iter() {
+            let path = format!("theme.{}", name);
+            let style = match s.get::<StyleConfig>(&path) {
+                Ok(config) => Theme::parse_style(&config),
+                Err(config::ConfigError::NotFound(_)) => continue,
+                Err(err) => Err(err.to_string()),
+            };
+            match (style, theme.style_mut(name)) {
+                (Ok(style), Some(field)) => *field = style,
+                (Err(err), _) => diagnostics.push(format!("{}: {}", path, err)),
+                _ => {}
+            }
+        }
+        theme
+    }
+
+    /// Parses the keybinding `name`, which is either a single key sequence
+    /// or a list of them. Bindings missing from the config file fall back to
+    /// the default config.
+    fn parse_binding(
+        s: &config::Config,
+        defaults: &config::Config,
+        name: &str,
+        diagnostics: &mut Vec<String>,
+    ) -> Vec<KeySequence> {
+        fn get_specs(s: &config::Config, path: &str) -> Option<Vec<String>> {
+            s.get::<String>(path)
+                .map(|spec| vec![spec])
+                .or_else(|_| s.get::<Vec<String>>(path))
+                .ok()
+        }
+
+        let path = format!("keybindings.{}", name);
+        let specs = get_specs(s, &path)
+            .or_else(|| get_specs(defaults, &path))
+            .unwrap_or_default();
+        if specs.iter().all(|spec| spec.trim().is_empty()) {
+            diagnostics.push(format!(
+                "keybindings.{} is empty, the action can't be used.",
+                name
+            ));
+            return Vec::new();
+        }
+        let mut sequences = Vec::new();
+        for spec in specs.iter().filter(|spec| !spec.trim().is_empty()) {
+            match Settings::parse_sequence(spec) {
+                Some(sequence) => sequences.push(sequence),
+                None => diagnostics.push(format!(
+                    "keybindings.{}: \"{}\" is not a valid key.",
+                    name, spec
+                )),
+            }
+        }
+        sequences
+    }
+
+    /// Parses space separated keys like `g g` or `ctrl-d`.
+    pub fn parse_sequence(spec: &str) -> Option<KeySequence> {
+        let sequence: KeySequence = spec
+            .split_whitespace()
+            .map(|key| Settings::parse_key(key.to_string()))
+            .collect();
+        if sequence.is_empty() || sequence.contains(&Key::Null) {
+            None
+        } else {
+            Some(sequence)
+        }
+    }
+
+    /// The inverse of `parse_sequence`.
+    pub fn sequence_to_string(sequence: &[Key]) -> String {
+        sequence
+            .iter()
+            .map(|key| Settings::key_to_string(*key))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Formats all sequences of a binding as a comma separated list.
+    pub fn sequences_to_string(sequences: &[KeySequence]) -> String {
+        sequences
+            .iter()
+            .map(|sequence| Settings::sequence_to_string(sequence))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Looks up the keys pressed so far in the keybindings and flavor keys.
+    pub fn resolve(&self, keys: &[Key]) -> KeyMatch {
+        let flavors = self
+            .flavors
+            .iter()
+            .enumerate()
+            .map(|(index, f)| (Action::SelectFlavor(index), vec![f.key]));
+        let mut exact = None;
+        let mut prefix = false;
+        for (action, sequence) in self
+            .key_bindings
+            .list()
+            .iter()
+            .flat_map(|(action, sequences)| {
+                sequences.iter().map(move |s| (*action, s.clone()))
+            })
+            .chain(flavors)
+        {
+            if sequence == keys {
+                exact = exact.or(Some(action));
+            } else if sequence.starts_with(keys) {
+                prefix = true;
+            }
+        }
+        match (exact, prefix) {
+            (Some(action), false) => KeyMatch::Exact(action),
+            (Some(action), true) => KeyMatch::Ambiguous(action),
+            (None, true) => KeyMatch::Prefix,
+            (None, false) => KeyMatch::None,
+        }
+    }
+
+    fn check_unknown_keys(s: &config::Config, diagnostics: &mut Vec<String>) {
+        let sections =
+            match s.clone().try_into::<HashMap<String, config::Value>>() {
+                Ok(sections) => sections,
+                Err(_) => return,
+            };
+        for (section, values) in sections.into_iter() {
+            let known: Vec<&str> = match section.as_str() {
+                "keybindings" => ACTIONS
+                    .iter()
+                    .map(|info| info.name)
+                    .chain(LEGACY_FLAVORS.iter().map(|(_, _, _, key)| *key))
+                    .collect(),
+                "network" => vec!["base_url", "timeout"],
+                "log" => vec!["debug", "max_size", "max_files"],
+                "theme" => {
+                    let mut keys = vec!["preset"];
+                    keys.extend(STYLE_NAMES.iter());
+                    keys
+                }
+                "paths" => {
+                    LEGACY_FLAVORS.iter().map(|(_, path, _, _)| *path).collect()
+                }
+                "flavors" => {
+                    let flavors = values.into_array().unwrap_or_default();
+                    for (index, flavor) in flavors.into_iter().enumerate() {
+                        let table = flavor.into_table().unwrap_or_default();
+                        for key in table.keys() {
+                            if !FLAVOR_KEYS.contains(&key.as_str()) {
+                                diagnostics.push(format!(
+                                    "Unknown key flavors[{}].{}.",
+                                    index, key
+                                ));
+                            }
+                        }
+                    }
+                    continue;
+                }
+                _ => {
+                    diagnostics.push(format!("Unknown section [{}].", section));
+                    continue;
+                }
+            };
+            for key in values.into_table().unwrap_or_default().keys() {
+                if !known.contains(&key.as_str()) {
+                    diagnostics
+                        .push(format!("Unknown key {}.{}.", section, key));
+                }
+            }
+        }
+    }
+
+    /// Reports key sequences that are bound to more than one action.
+    fn check_duplicate_keys(&self, diagnostics: &mut Vec<String>) {
+        let mut bindings: Vec<(String, KeySequence)> = self
+            .key_bindings
+            .list()
+            .iter()
+            .flat_map(|(action, sequences)| {
+                sequences
+                    .iter()
+                    .map(move |s| (action.name().to_string(), s.clone()))
+            })
+            .collect();
+        bindings.extend(
+            self.flavors
+                .iter()
+                .map(|f| (format!("select {}", f.name), vec![f.key])),
         );
-        let scroll_down_log = Settings::parse_key(
-            s.get::<String>("keybindings.scroll_down_log")
-                .unwrap_or("".to_string()),
+        for (index, (name, sequence)) in bindings.iter().enumerate() {
+            if sequence.contains(&Key::Null) {
+                continue;
+            }
+            if let Some((other, _)) =
+                bindings[index + 1..].iter().find(|(_, s)| s == sequence)
+            {
+                diagnostics.push(format!(
+                    "\"{}\" is bound to both {} and {}.",
+                    Settings::sequence_to_string(sequence),
+                    name,
+                    other
+                ));
+            }
+        }
+    }
+
+    /// Fails if a change bound a key sequence to a second action. Conflicts
+    /// in `before`, which existed before the change, are ignored.
+    fn check_new_duplicates(&self, before: &[String]) -> Result<(), String> {
+        let mut after = Vec::new();
+        self.check_duplicate_keys(&mut after);
+        match after.into_iter().find(|d| !before.contains(d)) {
+            Some(duplicate) => Err(duplicate),
+            None => Ok(()),
+        }
+    }
+
+    fn check_paths(&self, diagnostics: &mut Vec<String>) {
+        for flavor in self.flavors.iter().filter(|f| !f.path.is_empty()) {
+            if let Err(err) = Settings::validate_path(&flavor.path) {
+                diagnostics.push(format!("{}: {}", flavor.name, err));
+            }
+        }
+    }
+
+    /// Returns all values that can be edited in the settings tab.
+    pub fn entries(&self) -> Vec<SettingEntry> {
+        let mut entries = Vec::new();
+        for (index, flavor) in self.flavors.iter().enumerate() {
+            entries.push(SettingEntry {
+                field: SettingField::FlavorPath(index),
+                section: "Paths".to_string(),
+                name: flavor.name.clone(),
+                value: flavor.path.clone(),
+            });
+        }
+        for (index, flavor) in self.flavors.iter().enumerate() {
+            entries.push(SettingEntry {
+                field: SettingField::FlavorKey(index),
+                section: "Keybindings".to_string(),
+                name: format!("select {}", flavor.name),
+                value: Settings::key_to_string(flavor.key),
+            });
+        }
+        for (action, sequences) in self.key_bindings.list() {
+            entries.push(SettingEntry {
+                field: SettingField::KeyBinding(*action),
+                section: "Keybindings".to_string(),
+                name: action.name().to_string(),
+                value: Settings::sequences_to_string(sequences),
+            });
+        }
+        entries.push(SettingEntry {
+            field: SettingField::BaseUrl,
+            section: "Network".to_string(),
+            name: "base_url".to_string(),
+            value: self.network.base_url.clone(),
+        });
+        entries.push(SettingEntry {
+            field: SettingField::Timeout,
+            section: "Network".to_string(),
+            name: "timeout".to_string(),
+            value: self.network.timeout.to_string(),
+        });
+        entries.push(SettingEntry {
+            field: SettingField::LogDebug,
+            section: "Log".to_string(),
+            name: "debug".to_string(),
+            value: self.log.debug.to_string(),
+        });
+        entries
+    }
+
+    /// Validates `input` and stores it in `field`.
+    pub fn set(
+        &mut self,
+        field: SettingField,
+        input: &str,
+    ) -> Result<(), String> {
+        let input = input.trim();
+        let mut duplicates = Vec::new();
+        self.check_duplicate_keys(&mut duplicates);
+        match field {
+            SettingField::FlavorPath(index) => {
+                if !input.is_empty() {
+                    Settings::validate_path(input)?;
+                }
+                self.flavors[index].path = input.to_string();
+            }
+            SettingField::FlavorKey(index) => {
+                let key = Settings::validate_key(input)?;
+                let old = std::mem::replace(&mut self.flavors[index].key, key);
+                if let Err(err) = self.check_new_duplicates(&duplicates) {
+                    self.flavors[index].key = old;
+                    return Err(err);
+                }
+            }
+            SettingField::KeyBinding(action) => {
+                let mut sequences = Vec::new();
+                for spec in input.split(',').filter(|s| !s.trim().is_empty()) {
+                    match Settings::parse_sequence(spec) {
+                        Some(sequence) => sequences.push(sequence),
+                        None => {
+                            return Err(format!(
+                                "{} is not a valid key.",
+                                spec.trim()
+                            ))
+                        }
+                    }
+                }
+                let old = self.key_bindings.get(action).to_vec();
+                self.key_bindings.set(action, sequences);
+                if let Err(err) = self.check_new_duplicates(&duplicates) {
+                    self.key_bindings.set(action, old);
+                    return Err(err);
+                }
+            }
+            SettingField::BaseUrl => {
+                if !input.starts_with("http://")
+                    && !input.starts_with("https://")
+                {
+                    return Err(format!("{} is not a valid URL.", input));
+                }
+                self.network.base_url = input.trim_end_matches('/').to_string();
+            }
+            SettingField::Timeout => match input.parse::<u64>() {
+                Ok(timeout) if timeout > 0 => self.network.timeout = timeout,
+                _ => {
+                    return Err(format!(
+                        "{} is not a valid number of seconds.",
+                        input
+                    ))
+                }
+            },
+            SettingField::LogDebug => match input.parse::<bool>() {
+                Ok(debug) => self.log.debug = debug,
+                Err(_) => {
+                    return Err(format!("{} is not true or false.", input))
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn validate_key(input: &str) -> Result<Key, String> {
+        if input.is_empty() {
+            return Ok(Key::Null);
+        }
+        match Settings::parse_key(input.to_string()) {
+            Key::Null => Err(format!("{} is not a valid key.", input)),
+            key => Ok(key),
+        }
+    }
+
+    /// Writes the settings back into the config file. Comments and entries
+    /// unknown to the addon manager are kept as they are.
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        let content = fs::read_to_string(&self.config_path)?;
+        let mut doc = content.parse::<DocumentMut>()?;
+        doc.remove("paths");
+
+        let key_bindings = Settings::table(&mut doc, "keybindings");
+        for (_, _, _, key) in LEGACY_FLAVORS.iter() {
+            key_bindings.remove(key);
+        }
+        for (action, sequences) in self.key_bindings.list() {
+            let value = match sequences.as_slice() {
+                [sequence] => Settings::sequence_to_string(sequence).into(),
+                _ => Value::Array(
+                    sequences
+                        .iter()
+                        .map(|s| Settings::sequence_to_string(s))
+                        .collect::<Array>(),
+                ),
+            };
+            Settings::set_value(key_bindings, action.name(), value);
+        }
+
+        let network = Settings::table(&mut doc, "network");
+        Settings::set_value(
+            network,
+            "base_url",
+            self.network.base_url.clone().into(),
         );
-        let scroll_up_log = Settings::parse_key(
-            s.get::<String>("keybindings.scroll_up_log")
-                .unwrap_or("".to_string()),
+        Settings::set_value(
+            network,
+            "timeout",
+            (self.network.timeout as i64).into(),
         );
-        let key_bindings = KeyBindings {
-            update_addon: update_addon,
-            update_all_addons: update_all_addons,
-            remove_addon: remove_addon,
-            download_addon: download_addon,
-            install_addon: install_addon,
-            select_retail_version: select_retail_version,
-            select_classic_version: select_classic_version,
-            select_tbc_version: select_tbc_version,
-            search_addon: search_addon,
-            next_tab: next_tab,
-            prev_tab: prev_tab,
-            next_table_item: next_table_item,
-            prev_table_item: prev_table_item,
-            quit: quit,
-            scroll_down_log: scroll_down_log,
-            scroll_up_log: scroll_up_log,
-        };
-        Settings {
-            paths: paths,
-            key_bindings: key_bindings,
+
+        let log = Settings::table(&mut doc, "log");
+        Settings::set_value(log, "debug", self.log.debug.into());
+
+        if !doc.contains_array_of_tables("flavors") {
+            doc.insert("flavors", Item::ArrayOfTables(ArrayOfTables::new()));
+        }
+        let tables = doc["flavors"].as_array_of_tables_mut().unwrap();
+        while tables.len() > self.flavors.len() {
+            tables.remove(tables.len() - 1);
+        }
+        for (index, flavor) in self.flavors.iter().enumerate() {
+            if tables.get(index).is_none() {
+                tables.push(Table::new());
+            }
+            let table = tables.get_mut(index).unwrap();
+            Settings::set_value(table, "name", flavor.name.clone().into());
+            Settings::set_value(table, "path", flavor.path.clone().into());
+            Settings::set_value(
+                table,
+                "curse_flavor",
+                flavor.curse_flavor.clone().into(),
+            );
+            Settings::set_value(
+                table,
+                "key",
+                Settings::key_to_string(flavor.key).into(),
+            );
+        }
+        fs::write(&self.config_path, doc.to_string())?;
+        self.modified = Settings::modified(&self.config_path);
+        Ok(())
+    }
+
+    /// Returns the table `name` of `doc`, creating it if it doesn't exist.
+    fn table<'a>(doc: &'a mut DocumentMut, name: &str) -> &'a mut Table {
+        if !doc.contains_table(name) {
+            doc.insert(name, Item::Table(Table::new()));
+        }
+        doc[name].as_table_mut().unwrap()
+    }
+
+    /// Sets `key` of `table` while keeping the comments around the old value.
+    fn set_value(table: &mut Table, key: &str, new: Value) {
+        match table.get_mut(key).and_then(|item| item.as_value_mut()) {
+            Some(old) => {
+                let decor = old.decor().clone();
+                *old = new;
+                *old.decor_mut() = decor;
+            }
+            None => {
+                table.insert(key, Item::Value(new));
+            }
+        }
+    }
+
+    fn parse_flavors(
+        s: &config::Config,
+        diagnostics: &mut Vec<String>,
+    ) -> Vec<Flavor> {
+        match s.get::<Vec<FlavorConfig>>("flavors") {
+            Ok(flavors) => flavors
+                .into_iter()
+                .map(|f| {
+                    let key = Settings::parse_key(f.key.clone());
+                    if key == Key::Null && !f.key.is_empty() {
+                        diagnostics.push(format!(
+                            "{}: \"{}\" is not a valid key.",
+                            f.name, f.key
+                        ));
+                    }
+                    Flavor {
+                        name: f.name,
+                        path: f.path,
+                        curse_flavor: f.curse_flavor,
+                        key,
+                    }
+                })
+                .collect(),
+            Err(err) => {
+                if !matches!(err, config::ConfigError::NotFound(_)) {
+                    diagnostics.push(format!(
+                        "Couldn't read [[flavors]], using the [paths] section instead: {}",
+                        err
+                    ));
+                }
+                LEGACY_FLAVORS
+                    .iter()
+                    .map(|(name, path, curse_flavor, key)| Flavor {
+                        name: name.to_string(),
+                        path: s
+                            .get::<String>(&format!("paths.{}", path))
+                            .unwrap_or_default(),
+                        curse_flavor: curse_flavor.to_string(),
+                        key: Settings::parse_key(
+                            s.get::<String>(&format!("keybindings.{}", key))
+                                .unwrap_or_default(),
+                        ),
+                    })
+                    .collect()
+            }
         }
     }
 
@@ -154,32 +888,43 @@ impl Settings {
                 let path_string =
                     config_file_path.to_str().unwrap().to_string();
                 if !config_file_path.exists() {
-                    fs::copy("Config.toml", config_file_path)?;
+                    fs::write(config_file_path, DEFAULT_CONFIG)?;
                 }
                 Ok(path_string)
             }
-            None => {
-                panic!("Config directory not found!");
-            }
+            None => Err("Config directory not found!".into()),
+        }
+    }
+
+    /// The path of `filename` in `$XDG_DATA_HOME/wowAddonManager`, which
+    /// defaults to `~/.local/share/wowAddonManager`.
+    pub fn data_file(filename: &str) -> Result<PathBuf, Box<dyn Error>> {
+        match dirs::data_dir() {
+            Some(dir) => Ok(dir.join(APP_DIR).join(filename)),
+            None => Err("Data directory not found!".into()),
         }
     }
 
     pub fn parse_key(key: String) -> Key {
-        fn get_single_char(string: &str) -> char {
-            match string.chars().next() {
-                Some(c) => c,
-                None => '\0',
+        fn get_single_char(string: Option<&&str>) -> Option<char> {
+            let mut chars = string?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
             }
         }
 
-        match key.len() {
-            1 => Key::Char(get_single_char(key.as_str())),
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Key::Char(c),
             _ => {
                 let tokens: Vec<&str> = key.split('-').collect();
 
                 match tokens[0].to_lowercase().as_str() {
-                    "ctrl" => Key::Ctrl(get_single_char(tokens[1])),
-                    "alt" => Key::Alt(get_single_char(tokens[1])),
+                    "ctrl" => get_single_char(tokens.get(1))
+                        .map_or(Key::Null, Key::Ctrl),
+                    "alt" => get_single_char(tokens.get(1))
+                        .map_or(Key::Null, Key::Alt),
                     "left" => Key::Left,
                     "right" => Key::Right,
                     "up" => Key::Up,
@@ -190,9 +935,54 @@ impl Settings {
                     "pageup" => Key::PageUp,
                     "pagedown" => Key::PageDown,
                     "space" => Key::Char(' '),
+                    "enter" => Key::Char('\n'),
+                    "comma" => Key::Char(','),
                     _ => Key::Null,
                 }
             }
         }
     }
+
+    /// Checks that `path` is an existing, writable directory.
+    pub fn validate_path(path: &str) -> Result<(), String> {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return Err(format!("{} does not exist.", path)),
+        };
+        if !metadata.is_dir() {
+            return Err(format!("{} is not a directory.", path));
+        }
+        // The permission bits don't say whether the current user may write,
+        // so try it. The file is deleted when it is dropped.
+        if tempfile::Builder::new()
+            .prefix(".wowAddonManager")
+            .tempfile_in(path)
+            .is_err()
+        {
+            return Err(format!("{} is not writable.", path));
+        }
+        Ok(())
+    }
+
+    /// The inverse of `parse_key`.
+    pub fn key_to_string(key: Key) -> String {
+        match key {
+            Key::Char(' ') => "space".to_string(),
+            Key::Char('\n') => "enter".to_string(),
+            Key::Char(',') => "comma".to_string(),
+            Key::Char(c) => c.to_string(),
+            Key::Ctrl(c) => format!("ctrl-{}", c),
+            Key::Alt(c) => format!("alt-{}", c),
+            Key::Left => "left".to_string(),
+            Key::Right => "right".to_string(),
+            Key::Up => "up".to_string(),
+            Key::Down => "down".to_string(),
+            Key::Backspace => "backspace".to_string(),
+            Key::Delete => "del".to_string(),
+            Key::Esc => "esc".to_string(),
+            Key::PageUp => "pageup".to_string(),
+            Key::PageDown => "pagedown".to_string(),
+            _ => "".to_string(),
+        }
+    }
 }
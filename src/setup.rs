@@ -0,0 +1,192 @@
+use crate::addon_manager::AddonManager;
+use crate::settings::{Flavor, KeyBindings, Settings};
+use crate::theme::Theme;
+use termion::event::Key;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::widgets::{Block, Borders, Paragraph, Row, Table, Text};
+use tui::Frame;
+
+pub enum SetupResult {
+    Continue,
+    Finished,
+    Cancelled,
+}
+
+/// The first-run wizard. It asks for the AddOns path of every flavor and
+/// shows the keybindings before the config is saved.
+pub struct SetupWizard {
+    step: usize,
+    input: String,
+    error: Option<String>,
+}
+
+impl SetupWizard {
+    pub fn new(flavors: &[Flavor]) -> SetupWizard {
+        SetupWizard {
+            step: 0,
+            input: flavors.first().map(|f| f.path.clone()).unwrap_or_default(),
+            error: None,
+        }
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: Key,
+        flavors: &mut [Flavor],
+    ) -> SetupResult {
+        match key {
+            Key::Char('\n') => self.next(flavors),
+            Key::Char(c) if self.step < flavors.len() => {
+                self.input.push(c);
+                SetupResult::Continue
+            }
+            Key::Backspace => {
+                self.input.pop();
+                SetupResult::Continue
+            }
+            Key::Esc => self.back(flavors),
+            _ => SetupResult::Continue,
+        }
+    }
+
+    fn next(&mut self, flavors: &mut [Flavor]) -> SetupResult {
+        if self.step >= flavors.len() {
+            return SetupResult::Finished;
+        }
+        let path = self.input.trim().to_string();
+        if !path.is_empty() {
+            if let Err(err) = Settings::validate_path(&path).and_then(|_| {
+                AddonManager::init_addon_db(&path).map_err(|e| e.to_string())
+            }) {
+                self.error = Some(err);
+                return SetupResult::Continue;
+            }
+        }
+        flavors[self.step].path = path;
+        self.step += 1;
+        self.error = None;
+        self.input = flavors
+            .get(self.step)
+            .map(|f| f.path.clone())
+            .unwrap_or_default();
+        SetupResult::Continue
+    }
+
+    fn back(&mut self, flavors: &[Flavor]) -> SetupResult {
+        if self.step == 0 {
+            return SetupResult::Cancelled;
+        }
+        self.step -= 1;
+        self.error = None;
+        self.input = flavors[self.step].path.clone();
+        SetupResult::Continue
+    }
+
+    pub fn draw<B>(
+        &self,
+        frame: &mut Frame<B>,
+        area: Rect,
+        flavors: &[Flavor],
+        key_bindings: &KeyBindings,
+        theme: &Theme,
+    ) where
+        B: Backend,
+    {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Setup")
+            .border_style(theme.active);
+        frame.render_widget(block, area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Length(4),
+                    Constraint::Length(3),
+                    Constraint::Length(2),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let steps = flavors.len() + 1;
+
+        match flavors.get(self.step) {
+            Some(flavor) => {
+                let info = [Text::raw(format!(
+                    "Step {}/{}: Enter the path to the AddOns folder of {} ({}).\nLeave it empty to skip this flavor.\n\nEnter: continue, Esc: back",
+                    self.step + 1,
+                    steps,
+                    flavor.name,
+                    flavor.curse_flavor
+                ))];
+                frame.render_widget(
+                    Paragraph::new(info.iter()).wrap(true),
+                    chunks[0],
+                );
+                let input = [Text::raw(&self.input)];
+                let input = Paragraph::new(input.iter()).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(theme.active)
+                        .title("Path"),
+                );
+                frame.render_widget(input, chunks[1]);
+                if let Some(error) = &self.error {
+                    let error = [Text::styled(error, theme.error)];
+                    frame.render_widget(
+                        Paragraph::new(error.iter()).wrap(true),
+                        chunks[2],
+                    );
+                }
+            }
+            None => {
+                let info = [Text::raw(format!(
+                    "Step {}/{}: Review your keybindings. They can be changed in Config.toml later.\n\nEnter: save, Esc: back",
+                    steps, steps
+                ))];
+                frame.render_widget(
+                    Paragraph::new(info.iter()).wrap(true),
+                    chunks[0],
+                );
+                let mut bindings: Vec<Vec<String>> = key_bindings
+                    .list()
+                    .iter()
+                    .map(|(action, sequences)| {
+                        vec![
+                            action.name().to_string(),
+                            Settings::sequences_to_string(sequences),
+                        ]
+                    })
+                    .collect();
+                bindings.extend(flavors.iter().map(|f| {
+                    vec![
+                        format!("select {}", f.name),
+                        Settings::key_to_string(f.key),
+                    ]
+                }));
+                let rows = bindings
+                    .iter()
+                    .map(|b| Row::StyledData(b.iter(), theme.default));
+                let table = Table::new(["Action", "Key"].iter(), rows)
+                    .block(
+                        Block::default()
+                            .title("Keybindings")
+                            .borders(Borders::ALL),
+                    )
+                    .header_style(theme.active)
+                    .widths(&[
+                        Constraint::Percentage(50),
+                        Constraint::Percentage(50),
+                    ])
+                    .style(theme.default);
+                frame.render_widget(
+                    table,
+                    chunks[1].union(chunks[2]).union(chunks[3]),
+                );
+            }
+        }
+    }
+}
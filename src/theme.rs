@@ -0,0 +1,186 @@
+use serde::Deserialize;
+use tui::style::{Color, Modifier, Style};
+
+/// The styles used by all widgets. A theme starts from one of the presets
+/// and single styles can be overridden in the `[theme]` section.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub default: Style,
+    /// The selected row of a table.
+    pub hover: Style,
+    /// Focused widgets, table headers and the selected tab.
+    pub active: Style,
+    pub info: Style,
+    pub warning: Style,
+    pub error: Style,
+    /// Rows of the installed tab whose addon is up to date.
+    pub up_to_date: Style,
+    /// Rows of the installed tab whose addon has an update.
+    pub outdated: Style,
+}
+
+pub const PRESETS: [&str; 3] = ["dark", "light", "high-contrast"];
+pub const STYLE_NAMES: [&str; 8] = [
+    "default",
+    "hover",
+    "active",
+    "info",
+    "warning",
+    "error",
+    "up_to_date",
+    "outdated",
+];
+
+/// A style in the config file, either just a foreground color or a table
+/// with `fg`, `bg` and `modifiers`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum StyleConfig {
+    Color(String),
+    Style {
+        fg: Option<String>,
+        bg: Option<String>,
+        #[serde(default)]
+        modifiers: Vec<String>,
+    },
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            default: Style::default().fg(Color::Gray),
+            hover: Style::default().fg(Color::LightCyan),
+            active: Style::default().fg(Color::LightCyan),
+            info: Style::default(),
+            warning: Style::default().fg(Color::Yellow),
+            error: Style::default().fg(Color::Red),
+            up_to_date: Style::default().fg(Color::Gray),
+            outdated: Style::default().fg(Color::LightGreen),
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            default: Style::default().fg(Color::Black),
+            hover: Style::default().fg(Color::Blue),
+            active: Style::default().fg(Color::Blue),
+            info: Style::default().fg(Color::Black),
+            warning: Style::default().fg(Color::Rgb(175, 95, 0)),
+            error: Style::default().fg(Color::Red),
+            up_to_date: Style::default().fg(Color::Black),
+            outdated: Style::default().fg(Color::Green),
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        let bold = |color| Style::default().fg(color).modifier(Modifier::BOLD);
+        Theme {
+            default: Style::default().fg(Color::White),
+            hover: Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .modifier(Modifier::BOLD),
+            active: bold(Color::Yellow),
+            info: Style::default().fg(Color::White),
+            warning: bold(Color::LightYellow),
+            error: bold(Color::LightRed),
+            up_to_date: Style::default().fg(Color::White),
+            outdated: bold(Color::LightGreen),
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            _ => None,
+        }
+    }
+
+    pub fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        match name {
+            "default" => Some(&mut self.default),
+            "hover" => Some(&mut self.hover),
+            "active" => Some(&mut self.active),
+            "info" => Some(&mut self.info),
+            "warning" => Some(&mut self.warning),
+            "error" => Some(&mut self.error),
+            "up_to_date" => Some(&mut self.up_to_date),
+            "outdated" => Some(&mut self.outdated),
+            _ => None,
+        }
+    }
+
+    /// Builds a style from its config. Returns a description of the first
+    /// invalid value on failure.
+    pub fn parse_style(config: &StyleConfig) -> Result<Style, String> {
+        let parse_color = |name: &str| {
+            Theme::parse_color(name)
+                .ok_or(format!("\"{}\" is not a valid color.", name))
+        };
+        match config {
+            StyleConfig::Color(fg) => Ok(Style::default().fg(parse_color(fg)?)),
+            StyleConfig::Style { fg, bg, modifiers } => {
+                let mut style = Style::default();
+                if let Some(fg) = fg {
+                    style = style.fg(parse_color(fg)?);
+                }
+                if let Some(bg) = bg {
+                    style = style.bg(parse_color(bg)?);
+                }
+                for modifier in modifiers.iter() {
+                    let modifier = match modifier.as_str() {
+                        "bold" => Modifier::BOLD,
+                        "dim" => Modifier::DIM,
+                        "italic" => Modifier::ITALIC,
+                        "underlined" => Modifier::UNDERLINED,
+                        "reversed" => Modifier::REVERSED,
+                        "crossed_out" => Modifier::CROSSED_OUT,
+                        _ => {
+                            return Err(format!(
+                                "\"{}\" is not a valid modifier.",
+                                modifier
+                            ))
+                        }
+                    };
+                    style = style.modifier(style.modifier | modifier);
+                }
+                Ok(style)
+            }
+        }
+    }
+
+    /// Parses a color name like `light_cyan` or a hex color like `#ff8800`.
+    fn parse_color(name: &str) -> Option<Color> {
+        let color = match name.to_lowercase().as_str() {
+            "reset" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" => Color::Gray,
+            "dark_gray" => Color::DarkGray,
+            "light_red" => Color::LightRed,
+            "light_green" => Color::LightGreen,
+            "light_yellow" => Color::LightYellow,
+            "light_blue" => Color::LightBlue,
+            "light_magenta" => Color::LightMagenta,
+            "light_cyan" => Color::LightCyan,
+            "white" => Color::White,
+            hex => {
+                let hex = hex.strip_prefix('#')?;
+                if hex.len() != 6 {
+                    return None;
+                }
+                let channel =
+                    |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+                Color::Rgb(channel(0)?, channel(2)?, channel(4)?)
+            }
+        };
+        Some(color)
+    }
+}